@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::Path;
+
+use package_lib::{
+    Manifest, PROJECT_VERSION_PATH, Package, get_manifest_dir, lint_project, read_json,
+    read_project_version,
+};
+
+use crate::Result;
+
+/// Checks the manifest at `manifest_path` and the local `packages` for problems and prints them as
+/// a JSON array. The Unity version of the project is read from the project containing the
+/// manifest directory, if it has a project version file. Fails if there are problems.
+pub fn lint_manifest(manifest_path: &str, packages: &[Package]) -> Result<()> {
+    let manifest: Manifest = read_json(manifest_path)?;
+    let manifest_dir = get_manifest_dir(Path::new(manifest_path));
+    let manifest_dir = fs::canonicalize(manifest_dir).map_err(|source| package_lib::Error::Io {
+        path: manifest_dir.to_path_buf(),
+        operation: "resolve",
+        source,
+    })?;
+
+    let project_unity_version = match manifest_dir.parent() {
+        Some(project_path) if project_path.join(PROJECT_VERSION_PATH).is_file() => {
+            Some(read_project_version(project_path)?)
+        }
+        _ => None,
+    };
+    let issues = lint_project(
+        &manifest,
+        manifest_dir.as_path(),
+        packages,
+        project_unity_version.as_ref(),
+    )?;
+
+    println!("{}", serde_json::to_string_pretty(&issues)?);
+    if !issues.is_empty() {
+        return Err(format!("{} problem(s) found", issues.len()).into());
+    }
+    Ok(())
+}
@@ -1,9 +1,30 @@
-use clap::{self, Parser};
+use std::error;
+use std::process::ExitCode;
+use std::result;
 
+use clap::{self, Parser, Subcommand};
+
+mod lint;
 mod manifest_util;
+mod outdated;
+mod resolve;
+mod scopes;
+mod switch;
+mod testables;
 
+use lint::lint_manifest;
 use manifest_util::update_manifest_packages;
-use package_lib::{Package, Result, find_packages};
+use outdated::{UpdatePolicy, update_manifest_from_registries};
+use package_lib::{
+    DiscoveryOptions, Package, PackageSource, find_valid_packages, print_discovery_warning,
+    print_error,
+};
+use resolve::{ResolveOptions, print_resolution};
+use scopes::update_scopes;
+use switch::{SwitchOptions, check_file_references, switch_dependencies};
+use testables::update_testables;
+
+pub(crate) type Result<T> = result::Result<T, Box<dyn error::Error>>;
 
 #[derive(Parser, Debug)]
 #[clap(version, about, long_about = None)]
@@ -14,26 +35,190 @@ pub(crate) struct Options {
     /// Path to the manifest file
     #[clap(short, long, default_value = "Packages/manifest.json")]
     pub manifest_path: String,
+    /// Skip directories ignored by .gitignore files when searching for packages
+    #[clap(long)]
+    pub gitignore: bool,
+    /// Glob pattern of directories to skip when searching for packages, relative to the packages
+    /// directory
+    #[clap(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+    /// Also search for packages in git submodules
+    #[clap(long)]
+    pub submodules: bool,
+    /// Fail if a package manifest cannot be read instead of skipping the package
+    #[clap(long)]
+    pub strict: bool,
+    /// Update dependencies to versions in the scoped registries of the manifest instead of the
+    /// versions of local packages
+    #[clap(long)]
+    pub registry: bool,
+    /// Registry versions dependencies may be updated to
+    #[clap(long, value_enum, default_value_t = UpdatePolicy::SameMajor)]
+    pub policy: UpdatePolicy,
+    /// Also update dependencies to pre-release versions from registries
+    #[clap(long)]
+    pub pre_release: bool,
+    /// Add the scopes missing for the dependencies served by each scoped registry
+    #[clap(long)]
+    pub update_scopes: bool,
+    /// Also remove the scopes that match no dependency of their scoped registry, including the
+    /// dependencies of the local packages
+    #[clap(long, requires = "update_scopes")]
+    pub prune_scopes: bool,
+    /// JSON file that maps scoped registry names to glob patterns of the names of the packages
+    /// they serve, used instead of querying the registries
+    #[clap(long, value_name = "PATH", requires = "update_scopes")]
+    pub scopes_config: Option<String>,
+    /// Add the local packages that have a Tests folder or test assembly definitions to the
+    /// testables
+    #[clap(long)]
+    pub add_testables: bool,
+    /// Remove the testables that are neither a local package nor a dependency
+    #[clap(long)]
+    pub prune_testables: bool,
+    /// Check the manifest and the local packages for problems instead of updating, and print
+    /// them as JSON
+    #[clap(long)]
+    pub lint: bool,
     /// Verbose output
     #[clap(short, long)]
     verbose: bool,
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Switch dependencies between `file:` references to local packages and their versions
+    Switch(SwitchOptions),
+    /// Print the version Unity resolves each package to and the requirements that explain it
+    Resolve(ResolveOptions),
+}
+
+impl Options {
+    /// Returns whether the testables of the manifest are updated.
+    pub fn manages_testables(&self) -> bool {
+        self.add_testables || self.prune_testables
+    }
+
+    pub fn discovery_options(&self) -> DiscoveryOptions {
+        DiscoveryOptions {
+            respect_gitignore: self.gitignore,
+            exclude: self.exclude.clone(),
+            include_submodules: self.submodules,
+        }
+    }
 }
 
-fn main() -> Result<()> {
+fn run() -> Result<()> {
     let options = Options::parse();
 
-    let mut packages = find_packages(options.packages_path.as_str()).collect::<Vec<Package>>();
+    if let Some(Command::Resolve(resolve_options)) = &options.command {
+        let packages = find_valid_packages(
+            options.packages_path.as_str(),
+            &options.discovery_options(),
+            options.strict,
+            print_discovery_warning,
+        )?;
+        return print_resolution(&options.manifest_path, &packages, resolve_options);
+    }
+
+    if let Some(Command::Switch(switch_options)) = &options.command {
+        let matcher = switch_options.get_matcher()?;
+        if switch_options.check {
+            return check_file_references(&options.manifest_path, &matcher);
+        }
+        let packages = find_valid_packages(
+            options.packages_path.as_str(),
+            &options.discovery_options(),
+            options.strict,
+            print_discovery_warning,
+        )?;
+        return switch_dependencies(
+            &options.manifest_path,
+            &packages,
+            &matcher,
+            switch_options.to,
+        );
+    }
+
+    if options.lint {
+        let packages = find_valid_packages(
+            options.packages_path.as_str(),
+            &options.discovery_options(),
+            options.strict,
+            print_discovery_warning,
+        )?;
+        return lint_manifest(&options.manifest_path, &packages);
+    }
+
+    let packages = if !options.registry || options.manages_testables() || options.update_scopes {
+        find_local_packages(&options)?
+    } else {
+        Vec::new()
+    };
+
+    if options.registry {
+        update_manifest_from_registries(
+            &options.manifest_path,
+            options.policy,
+            options.pre_release,
+        )?;
+    } else {
+        update_manifest_packages(&options.manifest_path, &packages)?;
+    }
+
+    if options.manages_testables() {
+        update_testables(
+            &options.manifest_path,
+            &packages,
+            options.add_testables,
+            options.prune_testables,
+        )?;
+    }
+
+    if options.update_scopes {
+        update_scopes(
+            &options.manifest_path,
+            &packages,
+            options.scopes_config.as_deref(),
+            options.prune_scopes,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Finds the local packages and lists them if verbose output is enabled.
+fn find_local_packages(options: &Options) -> Result<Vec<Package>> {
+    let mut packages = find_valid_packages(
+        options.packages_path.as_str(),
+        &options.discovery_options(),
+        options.strict,
+        print_discovery_warning,
+    )?;
 
     if options.verbose {
         println!("{} package(s) found", packages.len());
 
         packages.sort_unstable();
         for package in packages.iter() {
-            println!("{} {}", package.name, package.version);
+            match package.source {
+                PackageSource::Directory => println!("{} {}", package.name, package.version),
+                source => println!("{} {} ({})", package.name, package.version, source),
+            }
         }
     }
 
-    update_manifest_packages(&options.manifest_path, &packages)?;
+    Ok(packages)
+}
 
-    Ok(())
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            print_error(error.as_ref());
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -1,35 +1,46 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
 
-use serde::{Deserialize, Serialize};
+use package_lib::{
+    Manifest, Package, PackageSource, Version, get_file_reference, read_json, write_json,
+};
 
-use package_lib::{Package, Result, Version, read_json, write_json};
+use crate::Result;
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ScopedRegistry {
-    name: String,
-    url: String,
-    scopes: Vec<String>,
-}
+/// Returns the versions of the package directories in `packages` by name. Tarballs are left out,
+/// as they are snapshots of packages rather than their sources. If several directories have the
+/// same package name, the highest version is used and a warning is printed.
+fn get_package_versions(packages: &[Package]) -> HashMap<&str, &Version> {
+    let mut package_map = HashMap::<&str, &Version>::new();
+    let mut duplicate_names = BTreeSet::<&str>::new();
+    for package in packages.iter() {
+        if package.source == PackageSource::Tarball {
+            continue;
+        }
+        match package_map.entry(package.name.as_str()) {
+            Entry::Occupied(mut entry) => {
+                duplicate_names.insert(package.name.as_str());
+                if package.version > **entry.get() {
+                    entry.insert(&package.version);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(&package.version);
+            }
+        }
+    }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Manifest {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    dependencies: Option<BTreeMap<String, String>>,
-    #[serde(rename = "enableLockFile")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    enable_lock_file: Option<bool>,
-    #[serde(rename = "resolutionStrategy")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    resolution_strategy: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "scopedRegistries")]
-    scoped_registries: Option<Vec<ScopedRegistry>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    testables: Option<Vec<String>>,
+    for name in duplicate_names {
+        eprintln!(
+            "warning: multiple local packages named {}; using the highest version {}",
+            name, package_map[name]
+        );
+    }
+    package_map
 }
 
 /// Reads manifest at `manifest_path` and updates existing manifest dependencies to versions
-/// specified by `packages`.
+/// specified by the package directories in `packages`. `file:` references are left unchanged.
 pub fn update_manifest_packages(manifest_path: &str, packages: &[Package]) -> Result<()> {
     let mut manifest: Manifest = read_json(manifest_path)?;
 
@@ -37,13 +48,14 @@ pub fn update_manifest_packages(manifest_path: &str, packages: &[Package]) -> Re
         return Ok(()); // Perhaps return an error here?
     };
 
-    let package_map = packages
-        .iter()
-        .map(|package| (package.name.as_str(), &package.version))
-        .collect::<HashMap<&str, &Version>>();
+    let package_map = get_package_versions(packages);
 
     let mut update_names = Vec::<(&str, &Version)>::new();
-    for k in dependencies.keys() {
+    for (k, value) in dependencies.iter() {
+        // References to local packages already point at their current version.
+        if get_file_reference(value).is_some() {
+            continue;
+        }
         if let Some((name, version)) = package_map.get_key_value(k.as_str()) {
             update_names.push((name, version));
         }
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use package_lib::{
+    Manifest, Registry, RegistryVersion, find_registry_token, get_npmrc_paths, read_json,
+    write_json,
+};
+
+use crate::Result;
+
+/// Registry versions a dependency may be updated to.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum UpdatePolicy {
+    /// Latest version
+    Latest,
+    /// Latest version with the same major version number
+    SameMajor,
+    /// Latest version with the same major and minor version numbers
+    Patch,
+}
+
+impl UpdatePolicy {
+    /// Returns whether a dependency at `current` may be updated to `candidate`.
+    fn allows(self, current: &RegistryVersion, candidate: &RegistryVersion) -> bool {
+        let (current, candidate) = (&current.version, &candidate.version);
+        match self {
+            UpdatePolicy::Latest => true,
+            UpdatePolicy::SameMajor => candidate.major == current.major,
+            UpdatePolicy::Patch => {
+                candidate.major == current.major && candidate.minor == current.minor
+            }
+        }
+    }
+}
+
+/// Dependency of the manifest that has newer versions in its registry.
+struct OutdatedDependency {
+    name: String,
+    current: RegistryVersion,
+    /// Newest version allowed by the update policy.
+    wanted: RegistryVersion,
+    latest: RegistryVersion,
+    registry: String,
+}
+
+/// Prints the outdated dependencies as a table with aligned columns, like `npm outdated` does.
+fn print_outdated_table(outdated: &[OutdatedDependency]) {
+    let mut rows = vec![[
+        String::from("Package"),
+        String::from("Current"),
+        String::from("Wanted"),
+        String::from("Latest"),
+        String::from("Registry"),
+    ]];
+    for dependency in outdated.iter() {
+        rows.push([
+            dependency.name.clone(),
+            dependency.current.to_string(),
+            dependency.wanted.to_string(),
+            dependency.latest.to_string(),
+            dependency.registry.clone(),
+        ]);
+    }
+
+    let mut widths = [0; 5];
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in rows.iter() {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Reads the manifest at `manifest_path`, queries the scoped registries for the versions of the
+/// dependencies in their scopes and updates each dependency to the newest version allowed by
+/// `policy`. Pre-releases are only considered if `include_pre_release` is set. Dependencies that
+/// are not versions, such as `file:` references, are left unchanged. Prints a table of the
+/// outdated dependencies.
+pub fn update_manifest_from_registries(
+    manifest_path: &str,
+    policy: UpdatePolicy,
+    include_pre_release: bool,
+) -> Result<()> {
+    let mut manifest: Manifest = read_json(manifest_path)?;
+    let npmrc_paths = get_npmrc_paths();
+    let mut registries = HashMap::<&str, Registry>::new();
+
+    let mut outdated = Vec::new();
+    for (name, value) in manifest.dependencies.iter().flatten() {
+        let Ok(current) = RegistryVersion::from_str(value) else {
+            continue;
+        };
+        let Some(scoped_registry) = manifest.get_scoped_registry(name) else {
+            continue;
+        };
+
+        let url = scoped_registry.url.as_str();
+        let registry = registries
+            .entry(url)
+            .or_insert_with(|| Registry::new(url, find_registry_token(url, &npmrc_paths)));
+        let versions = registry
+            .get_versions(name)?
+            .iter()
+            .filter_map(|version| RegistryVersion::from_str(version).ok())
+            .filter(|version| include_pre_release || !version.is_pre_release())
+            .collect::<Vec<_>>();
+
+        let Some(latest) = versions.iter().max().cloned() else {
+            continue;
+        };
+        let wanted = versions
+            .into_iter()
+            .filter(|version| *version > current && policy.allows(&current, version))
+            .max()
+            .unwrap_or_else(|| current.clone());
+        if latest <= current && wanted == current {
+            continue;
+        }
+
+        outdated.push(OutdatedDependency {
+            name: name.clone(),
+            current,
+            wanted,
+            latest,
+            registry: scoped_registry.name.clone(),
+        });
+    }
+
+    if outdated.is_empty() {
+        println!("all dependencies are up to date");
+        return Ok(());
+    }
+    print_outdated_table(outdated.as_slice());
+
+    let Some(dependencies) = &mut manifest.dependencies else {
+        return Ok(());
+    };
+    for dependency in outdated.into_iter() {
+        if dependency.wanted != dependency.current {
+            dependencies.insert(dependency.name, dependency.wanted.to_string());
+        }
+    }
+    write_json(manifest_path, &manifest)?;
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use clap::Args;
+use package_lib::{
+    Manifest, Package, Registry, RegistryVersion, ResolutionReason, ResolutionStrategy,
+    ResolvedPackage, find_registry_token, get_manifest_dir, get_npmrc_paths, read_json,
+    resolve_dependencies,
+};
+
+use crate::Result;
+
+#[derive(Args, Debug)]
+pub(crate) struct ResolveOptions {
+    /// Resolution strategy to simulate instead of the `resolutionStrategy` of the manifest:
+    /// lowest, highestPatch, highestMinor or highest
+    #[clap(long)]
+    pub strategy: Option<ResolutionStrategy>,
+    /// Query the scoped registries of the manifest for the versions available to indirect
+    /// dependencies
+    #[clap(long)]
+    pub registry: bool,
+}
+
+/// Returns the versions available in the scoped registries of `manifest` for the packages in
+/// `resolved_packages` that are resolved from the requirements of their dependents.
+fn get_available_versions(
+    manifest: &Manifest,
+    resolved_packages: &[ResolvedPackage],
+) -> Result<HashMap<String, Vec<RegistryVersion>>> {
+    let npmrc_paths = get_npmrc_paths();
+    let mut registries = HashMap::<&str, Registry>::new();
+
+    let mut available_versions = HashMap::new();
+    for package in resolved_packages.iter() {
+        if !matches!(package.reason, ResolutionReason::Requirements(_)) {
+            continue;
+        }
+        let Some(scoped_registry) = manifest.get_scoped_registry(&package.name) else {
+            continue;
+        };
+
+        let url = scoped_registry.url.as_str();
+        let registry = registries
+            .entry(url)
+            .or_insert_with(|| Registry::new(url, find_registry_token(url, &npmrc_paths)));
+        let versions = registry
+            .get_versions(&package.name)?
+            .iter()
+            .filter_map(|version| RegistryVersion::from_str(version).ok())
+            .collect();
+        available_versions.insert(package.name.clone(), versions);
+    }
+    Ok(available_versions)
+}
+
+/// Prints the version each package of the manifest at `manifest_path` resolves to with the local
+/// `packages`, along with the requirements that explain it.
+pub fn print_resolution(
+    manifest_path: &str,
+    packages: &[Package],
+    options: &ResolveOptions,
+) -> Result<()> {
+    let manifest: Manifest = read_json(manifest_path)?;
+    let strategy = match options.strategy {
+        Some(strategy) => strategy,
+        None => manifest
+            .resolution_strategy
+            .as_deref()
+            .map(ResolutionStrategy::from_str)
+            .transpose()?
+            .unwrap_or_default(),
+    };
+    let manifest_dir = get_manifest_dir(Path::new(manifest_path));
+
+    let mut resolved_packages =
+        resolve_dependencies(&manifest, manifest_dir, packages, &HashMap::new(), strategy)?;
+    if options.registry {
+        let available_versions = get_available_versions(&manifest, &resolved_packages)?;
+        resolved_packages = resolve_dependencies(
+            &manifest,
+            manifest_dir,
+            packages,
+            &available_versions,
+            strategy,
+        )?;
+    }
+
+    for package in resolved_packages.iter() {
+        println!("{} {} ({})", package.name, package.version, package.reason);
+        for requirement in package.requirements.iter() {
+            println!(
+                "  {} requires {}",
+                requirement.chain.join(" > "),
+                requirement.version
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,329 @@
+use std::collections::{BTreeMap, HashMap};
+
+use globset::GlobSet;
+use package_lib::{
+    Manifest, Package, Registry, ScopedRegistry, UNITY_PACKAGE_PREFIX, build_glob_set,
+    find_registry_token, get_file_reference, get_npmrc_paths, read_json, scope_matches, write_json,
+};
+
+use crate::Result;
+
+/// Minimum number of dot-separated segments of added scopes, such as `com.company`, so that a
+/// scope never captures the packages of other organizations.
+const MIN_SCOPE_SEGMENTS: usize = 2;
+
+/// Contents of a scopes config file: glob patterns of the names of the packages served by each
+/// scoped registry, keyed by registry name.
+type ScopesConfig = BTreeMap<String, Vec<String>>;
+
+/// Returns the matchers of the package name patterns of each registry in `registry_names`, in the
+/// same order, from the scopes config file at `config_path`.
+fn read_scopes_config(config_path: &str, registry_names: &[&str]) -> Result<Vec<GlobSet>> {
+    let config: ScopesConfig = read_json(config_path)?;
+    if let Some(name) = config
+        .keys()
+        .find(|name| !registry_names.contains(&name.as_str()))
+    {
+        return Err(format!(
+            "scopes config '{}' refers to unknown scoped registry {}",
+            config_path, name
+        )
+        .into());
+    }
+
+    let mut matchers = Vec::new();
+    for registry_name in registry_names {
+        let patterns = config.get(*registry_name).map_or(&[][..], Vec::as_slice);
+        matchers.push(build_glob_set(patterns)?);
+    }
+    Ok(matchers)
+}
+
+/// Returns the index of the registry in `registries` with the longest scope matching the package
+/// named `name`, which is the one Unity resolves the package from.
+fn get_scope_index(registries: &[ScopedRegistry], name: &str) -> Option<usize> {
+    registries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, registry)| {
+            registry
+                .get_match_length(name)
+                .map(|length| (length, index))
+        })
+        .max_by_key(|(length, _)| *length)
+        .map(|(_, index)| index)
+}
+
+/// Returns the index of the scoped registry of `manifest` that serves each registry dependency.
+/// Dependencies are assigned by the scopes config file at `config_path`, if given, then by the
+/// existing scopes, and otherwise to the first registry that has versions of them.
+fn get_registry_assignments(
+    manifest: &Manifest,
+    config_path: Option<&str>,
+) -> Result<HashMap<String, usize>> {
+    let registries = manifest.scoped_registries.as_deref().unwrap_or_default();
+    let registry_names = registries
+        .iter()
+        .map(|registry| registry.name.as_str())
+        .collect::<Vec<_>>();
+    let config_matchers = config_path
+        .map(|config_path| read_scopes_config(config_path, registry_names.as_slice()))
+        .transpose()?;
+    let npmrc_paths = get_npmrc_paths();
+    let mut clients = HashMap::<usize, Registry>::new();
+
+    let mut assignments = HashMap::new();
+    for (name, value) in manifest.dependencies.iter().flatten() {
+        if get_file_reference(value).is_some() || name.starts_with(UNITY_PACKAGE_PREFIX) {
+            continue;
+        }
+
+        let mut index = config_matchers
+            .as_ref()
+            .and_then(|matchers| matchers.iter().position(|matcher| matcher.is_match(name)));
+        if index.is_none() {
+            index = get_scope_index(registries, name);
+        }
+        if index.is_none() && config_matchers.is_none() {
+            for (registry_index, registry) in registries.iter().enumerate() {
+                let url = registry.url.as_str();
+                let client = clients
+                    .entry(registry_index)
+                    .or_insert_with(|| Registry::new(url, find_registry_token(url, &npmrc_paths)));
+                if !client.get_versions(name)?.is_empty() {
+                    index = Some(registry_index);
+                    break;
+                }
+            }
+        }
+
+        if let Some(index) = index {
+            assignments.insert(name.clone(), index);
+        }
+    }
+    Ok(assignments)
+}
+
+/// Returns the shortest prefix of `name` of at least two dot-separated segments that matches
+/// none of `others`, or `name` itself if every prefix does.
+fn get_minimal_scope(name: &str, others: &[&str]) -> String {
+    let segments = name.split('.').collect::<Vec<_>>();
+    for len in MIN_SCOPE_SEGMENTS..segments.len() {
+        let scope = segments[..len].join(".");
+        if !others.iter().any(|other| scope_matches(&scope, other)) {
+            return scope;
+        }
+    }
+    name.to_owned()
+}
+
+/// Returns the index of the scoped registry of `manifest` that each dependency of the local
+/// `packages` resolves from by the existing scopes, or `None` if no scope matches it. As all local
+/// packages are read, this includes the indirect dependencies through other local packages.
+/// Dependencies of the manifest itself are left out, as they are assigned by
+/// `get_registry_assignments`.
+fn get_local_dependency_assignments(
+    manifest: &Manifest,
+    packages: &[Package],
+) -> Result<HashMap<String, Option<usize>>> {
+    let registries = manifest.scoped_registries.as_deref().unwrap_or_default();
+    let mut assignments = HashMap::new();
+    for package in packages.iter() {
+        for name in package.read_dependencies()?.into_keys() {
+            if name.starts_with(UNITY_PACKAGE_PREFIX)
+                || manifest
+                    .dependencies
+                    .as_ref()
+                    .is_some_and(|dependencies| dependencies.contains_key(&name))
+            {
+                continue;
+            }
+            let index = get_scope_index(registries, name.as_str());
+            assignments.insert(name, index);
+        }
+    }
+    Ok(assignments)
+}
+
+/// Removes the scopes of `registry` that match none of `used_names` or are covered by another
+/// scope of the registry, and returns the removed scopes.
+fn prune_scopes(registry: &mut ScopedRegistry, used_names: &[&str]) -> Vec<String> {
+    let scopes = registry.scopes.clone();
+    let mut removed_scopes = Vec::new();
+    registry.scopes.retain(|scope| {
+        let is_used = used_names.iter().any(|name| scope_matches(scope, name));
+        let is_covered = scopes
+            .iter()
+            .any(|other| other != scope && scope_matches(other, scope));
+        if !is_used || is_covered {
+            removed_scopes.push(scope.clone());
+        }
+        is_used && !is_covered
+    });
+    removed_scopes
+}
+
+/// Adds the scopes missing for the dependencies served by each scoped registry of the manifest
+/// at `manifest_path`, choosing for each the shortest scope that captures no other dependency,
+/// including the dependencies of the local `packages` resolved from elsewhere. Registries are
+/// assigned by the scopes config file at `config_path` or by registry queries. If `prune` is set,
+/// scopes that match no dependency of their registry, including the dependencies of the local
+/// packages resolved through them, or are covered by another scope of the registry are removed.
+pub fn update_scopes(
+    manifest_path: &str,
+    packages: &[Package],
+    config_path: Option<&str>,
+    prune: bool,
+) -> Result<()> {
+    let mut manifest: Manifest = read_json(manifest_path)?;
+    let assignments = get_registry_assignments(&manifest, config_path)?;
+    let local_assignments = get_local_dependency_assignments(&manifest, packages)?;
+    let dependency_names = manifest
+        .dependencies
+        .iter()
+        .flatten()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    let Some(registries) = &mut manifest.scoped_registries else {
+        return Ok(());
+    };
+
+    let mut change_count = 0;
+    for (index, registry) in registries.iter_mut().enumerate() {
+        let (assigned, mut others): (Vec<&str>, Vec<&str>) = dependency_names
+            .iter()
+            .map(String::as_str)
+            .partition(|name| assignments.get(*name) == Some(&index));
+        let mut local_assigned = Vec::new();
+        for (name, local_index) in local_assignments.iter() {
+            if *local_index == Some(index) {
+                local_assigned.push(name.as_str());
+            } else {
+                others.push(name.as_str());
+            }
+        }
+
+        for name in assigned.iter() {
+            if registry
+                .scopes
+                .iter()
+                .any(|scope| scope_matches(scope, name))
+            {
+                continue;
+            }
+            let scope = get_minimal_scope(name, others.as_slice());
+            println!("added scope {} to {}", scope, registry.name);
+            registry.scopes.push(scope);
+            change_count += 1;
+        }
+
+        if prune {
+            let mut used_names = assigned;
+            used_names.extend(local_assigned);
+            for scope in prune_scopes(registry, used_names.as_slice()) {
+                println!("removed unused scope {} from {}", scope, registry.name);
+                change_count += 1;
+            }
+        }
+    }
+
+    if change_count > 0 {
+        write_json(manifest_path, &manifest)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use package_lib::{Manifest, Package, ScopedRegistry, find_packages, read_json};
+
+    use super::{get_minimal_scope, prune_scopes, update_scopes};
+
+    fn registry(scopes: &[&str]) -> ScopedRegistry {
+        ScopedRegistry {
+            name: "Company".to_owned(),
+            url: "https://registry.example.com".to_owned(),
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn chooses_shortest_scope_capturing_no_other_package() {
+        assert_eq!(get_minimal_scope("com.company.core", &[]), "com.company");
+        assert_eq!(
+            get_minimal_scope("com.company.core", &["com.company.tools"]),
+            "com.company.core"
+        );
+        assert_eq!(
+            get_minimal_scope("com.company.core.editor", &["com.company.tools"]),
+            "com.company.core"
+        );
+        assert_eq!(
+            get_minimal_scope("com.company", &["com.company.a"]),
+            "com.company"
+        );
+        // Scopes only match at segment boundaries.
+        assert_eq!(
+            get_minimal_scope("com.company.core", &["com.companyx.a"]),
+            "com.company"
+        );
+    }
+
+    #[test]
+    fn prunes_unused_and_covered_scopes() {
+        let mut company = registry(&["com.company", "com.company.core", "com.unused", "org.dep"]);
+        let removed = prune_scopes(&mut company, &["com.company.core.editor", "org.dep.lib"]);
+        assert_eq!(removed, ["com.company.core", "com.unused"]);
+        assert_eq!(company.scopes, ["com.company", "org.dep"]);
+
+        let mut unused = registry(&["com.other"]);
+        assert_eq!(prune_scopes(&mut unused, &[]), ["com.other"]);
+        assert!(unused.scopes.is_empty());
+    }
+
+    #[test]
+    fn adds_scopes_capturing_no_local_dependency_resolved_elsewhere() {
+        let fixture = tempfile::tempdir().unwrap();
+        let root = fixture.path();
+        let files = [
+            (
+                "Packages/manifest.json",
+                r#"{
+                    "dependencies": {"com.company.core": "1.0.0"},
+                    "scopedRegistries": [
+                        {"name": "Company", "url": "https://a.example.com", "scopes": []}
+                    ]
+                }"#,
+            ),
+            ("scopes.json", r#"{"Company": ["com.company.core"]}"#),
+            (
+                "Local/app/package.json",
+                r#"{"name": "com.studio.app", "version": "1.0.0",
+                    "dependencies": {"com.studio.ui": "1.0.0"}}"#,
+            ),
+            // Dependency of a local package that is only reachable through another one, resolved
+            // from the default registry.
+            (
+                "Local/ui/package.json",
+                r#"{"name": "com.studio.ui", "version": "1.0.0",
+                    "dependencies": {"com.company.legacy": "2.0.0"}}"#,
+            ),
+        ];
+        for (path, text) in files.iter() {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        let manifest_path = root.join("Packages/manifest.json");
+        let manifest_path = manifest_path.to_str().unwrap();
+        let config_path = root.join("scopes.json");
+        let packages = find_packages(root.join("Local")).collect::<Vec<Package>>();
+
+        update_scopes(manifest_path, &packages, config_path.to_str(), false).unwrap();
+        let manifest: Manifest = read_json(manifest_path).unwrap();
+        let registries = manifest.scoped_registries.unwrap();
+        assert_eq!(registries[0].scopes, ["com.company.core"]);
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+use globset::GlobSet;
+use package_lib::{
+    FILE_PREFIX, Manifest, Package, build_glob_set, get_file_reference, get_manifest_dir,
+    read_json, write_json,
+};
+
+use crate::Result;
+
+/// Kind of dependency reference packages are switched to.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum SwitchTarget {
+    /// `file:` reference to the local package directory or tarball
+    Local,
+    /// Version of the local package
+    Version,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct SwitchOptions {
+    /// Glob patterns of the names of the packages to switch
+    #[clap(default_value = "*")]
+    pub patterns: Vec<String>,
+    /// Kind of dependency reference to switch the packages to
+    #[clap(long, value_enum, default_value_t = SwitchTarget::Version, conflicts_with = "check")]
+    pub to: SwitchTarget,
+    /// Fail if a matching dependency is a `file:` reference instead of switching
+    #[clap(long)]
+    pub check: bool,
+}
+
+impl SwitchOptions {
+    /// Returns the matcher of the package name patterns.
+    pub fn get_matcher(&self) -> Result<GlobSet> {
+        Ok(build_glob_set(&self.patterns)?)
+    }
+}
+
+/// Returns the path of `path` relative to the directory `base`. Both paths must be absolute.
+fn get_relative_path(base: &Path, path: &Path) -> PathBuf {
+    let base = base.components().collect::<Vec<_>>();
+    let path = path.components().collect::<Vec<_>>();
+    let common_len = base
+        .iter()
+        .zip(path.iter())
+        .take_while(|(base, path)| base == path)
+        .count();
+
+    let mut relative_path = PathBuf::new();
+    for _ in common_len..base.len() {
+        relative_path.push(Component::ParentDir);
+    }
+    relative_path.extend(path[common_len..].iter());
+    relative_path
+}
+
+/// Returns the `file:` reference to `package` from the manifest in `manifest_dir`, with forward
+/// slashes like Unity writes them.
+fn format_file_reference(manifest_dir: &Path, package: &Package) -> String {
+    let relative_path = get_relative_path(manifest_dir, package.path.as_path());
+    let components = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    format!("{}{}", FILE_PREFIX, components.join("/"))
+}
+
+/// Fails if a dependency of the manifest at `manifest_path` whose name matches `matcher` is a
+/// `file:` reference, and prints each such dependency.
+pub fn check_file_references(manifest_path: &str, matcher: &GlobSet) -> Result<()> {
+    let manifest: Manifest = read_json(manifest_path)?;
+    let mut reference_count = 0;
+    for (name, value) in manifest.dependencies.iter().flatten() {
+        if matcher.is_match(name) && get_file_reference(value).is_some() {
+            eprintln!("{}: {}", name, value);
+            reference_count += 1;
+        }
+    }
+
+    if reference_count > 0 {
+        return Err(format!(
+            "{} dependency(ies) in '{}' reference local packages",
+            reference_count, manifest_path
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Switches the dependencies of the manifest at `manifest_path` whose name matches `matcher` and
+/// that are in `packages` to `target`: a `file:` reference relative to the manifest directory or
+/// the version of the package. Dependencies without a local package are left unchanged.
+pub fn switch_dependencies(
+    manifest_path: &str,
+    packages: &[Package],
+    matcher: &GlobSet,
+    target: SwitchTarget,
+) -> Result<()> {
+    let mut manifest: Manifest = read_json(manifest_path)?;
+    let Some(dependencies) = &mut manifest.dependencies else {
+        return Ok(());
+    };
+
+    let manifest_dir = get_manifest_dir(Path::new(manifest_path));
+    let manifest_dir = fs::canonicalize(manifest_dir).map_err(|source| package_lib::Error::Io {
+        path: manifest_dir.to_path_buf(),
+        operation: "resolve",
+        source,
+    })?;
+    let package_map = packages
+        .iter()
+        .map(|package| (package.name.as_str(), package))
+        .collect::<HashMap<&str, &Package>>();
+
+    let mut switch_count = 0;
+    for (name, value) in dependencies.iter_mut() {
+        if !matcher.is_match(name) {
+            continue;
+        }
+        let Some(package) = package_map.get(name.as_str()) else {
+            continue;
+        };
+
+        let switched_value = match target {
+            SwitchTarget::Local => format_file_reference(manifest_dir.as_path(), package),
+            SwitchTarget::Version => package.version.to_string(),
+        };
+        if *value == switched_value {
+            continue;
+        }
+        println!("{}: {} -> {}", name, value, switched_value);
+        *value = switched_value;
+        switch_count += 1;
+    }
+
+    if switch_count > 0 {
+        write_json(manifest_path, &manifest)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use package_lib::{Manifest, Package, PackageSource, is_unity_ignored, read_json, write_json};
+use serde_json::Value;
+
+use crate::Result;
+
+/// Name of the folder Unity packages keep their tests in.
+const TESTS_FOLDER_NAME: &str = "Tests";
+
+/// Filename extension of assembly definitions.
+const ASSEMBLY_DEFINITION_EXTENSION: &str = "asmdef";
+
+/// Define constraint of test assemblies.
+const TEST_DEFINE_CONSTRAINT: &str = "UNITY_INCLUDE_TESTS";
+
+/// Legacy optional Unity reference of test assemblies.
+const TEST_OPTIONAL_REFERENCE: &str = "TestAssemblies";
+
+/// Assemblies referenced by test assemblies.
+const TEST_RUNNER_REFERENCES: [&str; 2] = ["UnityEngine.TestRunner", "UnityEditor.TestRunner"];
+
+/// Returns whether the JSON array `field` of the assembly definition `definition` contains any of
+/// `values`.
+fn contains_any(definition: &Value, field: &str, values: &[&str]) -> bool {
+    definition[field]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .any(|value| values.contains(&value))
+}
+
+/// Returns whether the assembly definition at `path` defines a test assembly. Unreadable files are
+/// not test assemblies.
+fn is_test_assembly_definition(path: &Path) -> bool {
+    let Ok(definition) = read_json::<_, Value>(path) else {
+        return false;
+    };
+    contains_any(&definition, "defineConstraints", &[TEST_DEFINE_CONSTRAINT])
+        || contains_any(
+            &definition,
+            "optionalUnityReferences",
+            &[TEST_OPTIONAL_REFERENCE],
+        )
+        || contains_any(&definition, "references", &TEST_RUNNER_REFERENCES)
+}
+
+/// Returns whether the directory at `path` contains a test assembly definition, skipping the
+/// directories Unity ignores.
+fn contains_test_assembly(path: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(path) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            return false;
+        };
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            return !name.to_str().is_some_and(is_unity_ignored) && contains_test_assembly(&path);
+        }
+        path.extension()
+            .is_some_and(|extension| extension == ASSEMBLY_DEFINITION_EXTENSION)
+            && is_test_assembly_definition(path.as_path())
+    })
+}
+
+/// Returns whether the package directory at `path` has tests: a `Tests` folder or a test
+/// assembly definition.
+fn has_tests(path: &Path) -> bool {
+    path.join(TESTS_FOLDER_NAME).is_dir() || contains_test_assembly(path)
+}
+
+/// Updates the `testables` of the manifest at `manifest_path` and keeps them sorted. If `add` is
+/// set, the local `packages` that have tests are added. If `prune` is set, entries that are
+/// neither a local package nor a dependency of the manifest are removed.
+pub fn update_testables(
+    manifest_path: &str,
+    packages: &[Package],
+    add: bool,
+    prune: bool,
+) -> Result<()> {
+    let mut manifest: Manifest = read_json(manifest_path)?;
+    let original_testables = manifest.testables.clone().unwrap_or_default();
+    let mut testables = original_testables.clone();
+
+    if add {
+        let mut packages = packages.iter().collect::<Vec<_>>();
+        packages.sort_unstable_by_key(|package| package.name.as_str());
+        for package in packages {
+            if package.source == PackageSource::Tarball
+                || testables.contains(&package.name)
+                || !has_tests(package.path.as_path())
+            {
+                continue;
+            }
+            println!("added testable {}", package.name);
+            testables.push(package.name.clone());
+        }
+    }
+
+    if prune {
+        let mut existing_names = packages
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect::<HashSet<_>>();
+        existing_names.extend(
+            manifest
+                .dependencies
+                .iter()
+                .flatten()
+                .map(|(name, _)| name.as_str()),
+        );
+        testables.retain(|name| {
+            let exists = existing_names.contains(name.as_str());
+            if !exists {
+                println!("removed testable {}", name);
+            }
+            exists
+        });
+    }
+
+    testables.sort_unstable();
+    testables.dedup();
+    if testables != original_testables {
+        manifest.testables = Some(testables);
+        write_json(manifest_path, &manifest)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+use jwalk::{ClientState, DirEntry};
+
+use crate::{Error, Result};
+
+/// Filename of git ignore files.
+const GITIGNORE_FILENAME: &str = ".gitignore";
+
+/// Options of package discovery.
+#[derive(Default, Clone, Debug)]
+pub struct DiscoveryOptions {
+    /// Skip directories ignored by `.gitignore` files in the packages directory and the
+    /// directories above it up to the repository root.
+    pub respect_gitignore: bool,
+    /// Glob patterns of directories to skip, relative to the packages directory.
+    pub exclude: Vec<String>,
+    /// Search git submodules for packages.
+    pub include_submodules: bool,
+}
+
+/// Returns whether Unity ignores the directory named `name`. Unity ignores hidden directories,
+/// directories ending in `~`, directories named `cvs` and directories ending in `.tmp`.
+pub fn is_unity_ignored(name: &str) -> bool {
+    name.starts_with('.')
+        || name.ends_with('~')
+        || name.eq_ignore_ascii_case("cvs")
+        || name.ends_with(".tmp")
+}
+
+/// Returns a matcher of any of the glob `patterns`. Fails if a pattern is invalid.
+pub fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.iter() {
+        let glob = Glob::new(pattern).map_err(|source| Error::Pattern {
+            pattern: pattern.clone(),
+            source,
+        })?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|source| Error::Pattern {
+        pattern: patterns.join(","),
+        source,
+    })
+}
+
+/// `.gitignore` matchers of a directory and its ancestors, innermost last.
+pub(crate) type GitignoreStack = Vec<Arc<Gitignore>>;
+
+/// State of the package walker passed from a directory to its children.
+#[derive(Default, Clone, Debug)]
+pub(crate) struct WalkState {
+    pub gitignores: GitignoreStack,
+    /// Whether the directory is inside a git submodule.
+    pub in_submodule: bool,
+}
+
+/// Decides which directories the package walker descends into.
+#[derive(Debug)]
+pub(crate) struct DirectoryFilter {
+    root: PathBuf,
+    respect_gitignore: bool,
+    exclude: GlobSet,
+    include_submodules: bool,
+}
+
+impl DirectoryFilter {
+    /// Returns a filter that only applies Unity's ignore rules.
+    pub fn unity(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            respect_gitignore: false,
+            exclude: GlobSet::empty(),
+            include_submodules: false,
+        }
+    }
+
+    pub fn new(root: &Path, options: &DiscoveryOptions) -> Result<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            respect_gitignore: options.respect_gitignore,
+            exclude: build_glob_set(&options.exclude)?,
+            include_submodules: options.include_submodules,
+        })
+    }
+
+    pub fn include_submodules(&self) -> bool {
+        self.include_submodules
+    }
+
+    /// Returns whether the directory at `path` with the entries `children` is the root of a git
+    /// submodule, which has a `.git` file or directory. The walked directory itself is not
+    /// considered a submodule.
+    pub fn is_submodule<C: ClientState>(
+        &self,
+        path: &Path,
+        children: &[jwalk::Result<DirEntry<C>>],
+    ) -> bool {
+        path != self.root
+            && children
+                .iter()
+                .flatten()
+                .any(|dir_entry| dir_entry.file_name == ".git")
+    }
+
+    /// Returns the `.gitignore` matchers of the directories above the root up to the repository
+    /// root.
+    pub fn get_ancestor_gitignores(&self) -> GitignoreStack {
+        let mut gitignores = GitignoreStack::new();
+        if !self.respect_gitignore {
+            return gitignores;
+        }
+
+        for dir in self.root.ancestors().skip(1) {
+            self.push_gitignore(&mut gitignores, dir);
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        gitignores.reverse();
+        gitignores
+    }
+
+    /// Adds the matcher of the `.gitignore` file in `dir` to `gitignores`, if there is one.
+    pub fn push_gitignore(&self, gitignores: &mut GitignoreStack, dir: &Path) {
+        if !self.respect_gitignore {
+            return;
+        }
+        let path = dir.join(GITIGNORE_FILENAME);
+        if !path.is_file() {
+            return;
+        }
+        // Invalid lines are skipped, like git does.
+        let (gitignore, _error) = Gitignore::new(path);
+        gitignores.push(Arc::new(gitignore));
+    }
+
+    /// Returns whether the directory at `path` and everything in it is skipped.
+    pub fn is_skipped(&self, path: &Path, gitignores: &GitignoreStack) -> bool {
+        let name = path.file_name().and_then(|name| name.to_str());
+        if name.is_some_and(is_unity_ignored) {
+            return true;
+        }
+
+        if let Ok(relative_path) = path.strip_prefix(self.root.as_path())
+            && self.exclude.is_match(relative_path)
+        {
+            return true;
+        }
+
+        for gitignore in gitignores.iter().rev() {
+            match gitignore.matched(path, true) {
+                Match::None => continue,
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,292 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::semver::VersionError;
+use crate::unity::UnityVersionError;
+
+/// Error of a package-lib operation along with the path of the file it concerns.
+#[derive(Debug)]
+pub enum Error {
+    /// Opening, reading or writing a file failed.
+    Io {
+        path: PathBuf,
+        /// Operation that failed, such as "open" or "write".
+        operation: &'static str,
+        source: io::Error,
+    },
+    /// A file is not valid JSON, does not have the expected structure or could not be serialized.
+    Json {
+        path: PathBuf,
+        /// Operation that failed, such as "parse" or "serialize".
+        operation: &'static str,
+        source: serde_json::Error,
+    },
+    /// A version in a file is not a valid semantic version.
+    Version {
+        path: PathBuf,
+        /// Version text that could not be parsed.
+        value: String,
+        source: VersionError,
+    },
+    /// A Unity version in a file is not valid.
+    UnityVersion {
+        path: PathBuf,
+        /// Version text that could not be parsed.
+        value: String,
+        source: UnityVersionError,
+    },
+    /// A glob pattern is invalid.
+    Pattern {
+        pattern: String,
+        source: globset::Error,
+    },
+    /// A request to a package registry failed.
+    Registry {
+        url: String,
+        /// Operation that failed, such as "query" or "publish to".
+        operation: &'static str,
+        source: ureq::Error,
+    },
+    /// Packages could not be read during a strict package discovery.
+    InvalidPackages {
+        /// Number of packages that could not be read.
+        count: usize,
+    },
+}
+
+impl Error {
+    /// Returns a function that wraps an I/O error of `operation` on the file at `path`.
+    pub(crate) fn io(path: &Path, operation: &'static str) -> impl FnOnce(io::Error) -> Self {
+        let path = path.to_path_buf();
+        move |source| Error::Io {
+            path,
+            operation,
+            source,
+        }
+    }
+
+    /// Returns a function that wraps a JSON error of `operation` on the file at `path`.
+    pub(crate) fn json(
+        path: &Path,
+        operation: &'static str,
+    ) -> impl FnOnce(serde_json::Error) -> Self {
+        let path = path.to_path_buf();
+        move |source| Error::Json {
+            path,
+            operation,
+            source,
+        }
+    }
+
+    /// Returns a function that wraps an error of a request of `operation` to `url`.
+    pub(crate) fn registry(url: &str, operation: &'static str) -> impl FnOnce(ureq::Error) -> Self {
+        let url = url.to_owned();
+        move |source| Error::Registry {
+            url,
+            operation,
+            source,
+        }
+    }
+
+    /// Returns the path of the file the error concerns, if it concerns a file.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Io { path, .. }
+            | Error::Json { path, .. }
+            | Error::Version { path, .. }
+            | Error::UnityVersion { path, .. } => Some(path),
+            Error::Pattern { .. } | Error::Registry { .. } | Error::InvalidPackages { .. } => None,
+        }
+    }
+
+    /// Returns a suggestion on how to fix the cause of the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Error::Io { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => Some("check that the path exists"),
+                io::ErrorKind::PermissionDenied => Some("check the permissions of the file"),
+                _ => None,
+            },
+            Error::Json { source, .. } if source.is_syntax() || source.is_eof() => {
+                Some("fix the JSON syntax at the given line and column")
+            }
+            Error::Json { source, .. } if source.is_data() => {
+                Some("check that all required fields are present and have the right type")
+            }
+            Error::Json { .. } => None,
+            Error::Version { .. } => Some("versions must have the form MAJOR.MINOR.PATCH"),
+            Error::UnityVersion { .. } => Some(
+                "Unity versions must have the form YEAR.MINOR or YEAR.MINOR.PATCH{a,b,f,p}BUILD",
+            ),
+            Error::Pattern { .. } => Some("check the glob syntax, such as unclosed brackets"),
+            Error::Registry { source, .. } => match source {
+                ureq::Error::StatusCode(401 | 403) => {
+                    Some("check the registry token, the _authToken of the registry in .npmrc")
+                }
+                ureq::Error::Io(_) | ureq::Error::HostNotFound | ureq::Error::ConnectionFailed => {
+                    Some("check the registry URL and that the registry is reachable")
+                }
+                _ => None,
+            },
+            Error::InvalidPackages { .. } => {
+                Some("fix or remove the packages in the warnings above")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Json { source, .. } => Some(source),
+            Error::Version { source, .. } => Some(source),
+            Error::UnityVersion { source, .. } => Some(source),
+            Error::Pattern { source, .. } => Some(source),
+            Error::Registry { source, .. } => Some(source),
+            Error::InvalidPackages { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io {
+                path,
+                operation,
+                source,
+            } => write!(
+                formatter,
+                "failed to {} '{}': {}",
+                operation,
+                path.display(),
+                source
+            ),
+            Error::Json {
+                path,
+                operation,
+                source,
+            } => write!(
+                formatter,
+                "failed to {} '{}': {}",
+                operation,
+                path.display(),
+                source
+            ),
+            Error::Version {
+                path,
+                value,
+                source,
+            } => write!(
+                formatter,
+                "invalid version '{}' in '{}': {}",
+                value,
+                path.display(),
+                source
+            ),
+            Error::UnityVersion {
+                path,
+                value,
+                source,
+            } => write!(
+                formatter,
+                "invalid Unity version '{}' in '{}': {}",
+                value,
+                path.display(),
+                source
+            ),
+            Error::Pattern { pattern, source } => {
+                write!(formatter, "invalid pattern '{}': {}", pattern, source)
+            }
+            Error::Registry {
+                url,
+                operation,
+                source,
+            } => write!(formatter, "failed to {} '{}': {}", operation, url, source),
+            Error::InvalidPackages { count } => {
+                write!(formatter, "{} package(s) could not be read", count)
+            }
+        }
+    }
+}
+
+/// Returns a suggestion on how to fix `error`, if it is an error of this library that has one.
+pub fn get_error_hint(error: &(dyn error::Error + 'static)) -> Option<&'static str> {
+    if let Some(error) = error.downcast_ref::<Error>() {
+        return error.hint();
+    }
+    error
+        .downcast_ref::<DiscoveryError>()
+        .and_then(DiscoveryError::hint)
+}
+
+/// Prints `error` along with a hint on how to fix it, if there is one.
+pub fn print_error(error: &(dyn error::Error + 'static)) {
+    eprintln!("error: {}", error);
+    if let Some(hint) = get_error_hint(error) {
+        eprintln!("hint: {}", hint);
+    }
+}
+
+/// Prints a warning about a package that cannot be read along with a hint on how to fix it, if
+/// there is one.
+pub fn print_discovery_warning(error: &DiscoveryError) {
+    eprintln!("warning: {}", error);
+    if let Some(hint) = error.hint() {
+        eprintln!("hint: {}", hint);
+    }
+}
+
+/// Error of a package that cannot be read during package discovery.
+#[derive(Debug)]
+pub enum DiscoveryError {
+    /// A directory could not be read.
+    Walk(jwalk::Error),
+    /// A package manifest could not be read or is invalid.
+    Manifest(Error),
+}
+
+impl DiscoveryError {
+    /// Returns the path of the directory or manifest the error concerns, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            DiscoveryError::Walk(error) => error.path(),
+            DiscoveryError::Manifest(error) => error.path(),
+        }
+    }
+
+    /// Returns a suggestion on how to fix the cause of the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            DiscoveryError::Walk(error) => match error.io_error().map(io::Error::kind) {
+                Some(io::ErrorKind::NotFound) => Some("check that the path exists"),
+                Some(io::ErrorKind::PermissionDenied) => {
+                    Some("check the permissions of the directory")
+                }
+                _ => None,
+            },
+            DiscoveryError::Manifest(error) => error.hint(),
+        }
+    }
+}
+
+impl error::Error for DiscoveryError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DiscoveryError::Walk(error) => Some(error),
+            DiscoveryError::Manifest(error) => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for DiscoveryError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiscoveryError::Walk(error) => write!(formatter, "failed to read directory: {}", error),
+            DiscoveryError::Manifest(error) => write!(formatter, "invalid package: {}", error),
+        }
+    }
+}
@@ -5,7 +5,7 @@ use std::path::Path;
 use serde::{Serialize, de};
 use unicode_bom::Bom;
 
-use crate::Result;
+use crate::{Error, Result};
 
 pub trait Trim {
     /// Trims whitespace from the beginning and end of the string.
@@ -42,16 +42,21 @@ impl NormalizeLineEndings for String {
 
 /// Attamps to open a file read-only and skips the BOM if present.
 pub fn open_file_skip_bom<P: AsRef<Path>>(path: P) -> Result<File> {
-    let mut file = File::open(path)?;
+    let path = path.as_ref();
+    let mut file = File::open(path).map_err(Error::io(path, "open"))?;
     let bom_len = Bom::from(&mut file).len();
-    file.seek(SeekFrom::Start(bom_len as u64))?;
+    file.seek(SeekFrom::Start(bom_len as u64))
+        .map_err(Error::io(path, "read"))?;
     Ok(file)
 }
 
 /// Attempts to read a file into a string and skips the BOM if present.
 pub fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
     let mut buffer = String::new();
-    open_file_skip_bom(path)?.read_to_string(&mut buffer)?;
+    open_file_skip_bom(path)?
+        .read_to_string(&mut buffer)
+        .map_err(Error::io(path, "read"))?;
     Ok(buffer)
 }
 
@@ -61,9 +66,9 @@ where
     P: AsRef<Path>,
     T: de::DeserializeOwned,
 {
-    let mut buffer = String::new();
-    open_file_skip_bom(path)?.read_to_string(&mut buffer)?;
-    Ok(serde_json::from_str(&buffer)?)
+    let path = path.as_ref();
+    let buffer = read_to_string(path)?;
+    serde_json::from_str(&buffer).map_err(Error::json(path, "parse"))
 }
 
 /// Attempts to write a JSON value to a file.
@@ -72,9 +77,12 @@ where
     P: AsRef<Path>,
     T: ?Sized + Serialize,
 {
-    let mut writer = BufWriter::new(File::create(path)?);
-    serde_json::to_writer_pretty(&mut writer, data)?;
-    let _ = writer.write(b"\n")?;
-    writer.flush()?;
+    let path = path.as_ref();
+    let mut writer = BufWriter::new(File::create(path).map_err(Error::io(path, "create"))?);
+    serde_json::to_writer_pretty(&mut writer, data).map_err(Error::json(path, "serialize"))?;
+    writer
+        .write_all(b"\n")
+        .and_then(|_| writer.flush())
+        .map_err(Error::io(path, "write"))?;
     Ok(())
 }
@@ -1,26 +1,128 @@
-use std::error;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::result;
+use std::str::FromStr;
+use std::sync::Arc;
 
-use jwalk::{DirEntryIter, WalkDir};
+use jwalk::{DirEntryIter, WalkDirGeneric};
 use serde::Deserialize;
 
+mod discovery;
+mod error;
 mod io;
+mod lint;
+mod manifest;
+mod pack;
+mod registry;
+mod resolution;
 mod semver;
+mod tarball;
+mod unity;
 
+use discovery::{DirectoryFilter, WalkState};
+use tarball::read_tarball_manifest_text;
+
+pub use discovery::{DiscoveryOptions, build_glob_set, is_unity_ignored};
+pub use error::{DiscoveryError, Error, get_error_hint, print_discovery_warning, print_error};
 pub use io::*;
-pub use semver::Version;
+pub use lint::{LintIssue, lint_project};
+pub use manifest::{
+    FILE_PREFIX, Manifest, ScopedRegistry, UNITY_PACKAGE_PREFIX, get_file_reference,
+    get_manifest_dir, scope_matches,
+};
+pub use pack::{PackContents, get_pack_contents, pack_package, write_package_tarball};
+pub use registry::{Registry, find_registry_token, get_npmrc_paths};
+pub use resolution::{
+    Requirement, ResolutionReason, ResolutionStrategy, ResolvedPackage, resolve_dependencies,
+};
+pub use semver::{Position, RegistryVersion, Version, VersionError};
+pub use tarball::{TARBALL_MANIFEST_PATH, is_tarball};
+pub use unity::{
+    PROJECT_VERSION_PATH, ReleaseType, UnityRelease, UnityVersion, UnityVersionError,
+    read_project_version,
+};
 
-pub type Result<T> = result::Result<T, Box<dyn error::Error>>;
+pub type Result<T> = result::Result<T, Error>;
 
 /// Filename of the package manifest file "package.json".
 pub static PACKAGE_MANIFEST_FILENAME: &str = "package.json";
 
 #[derive(Deserialize, Debug)]
-struct PackageManifest {
+pub(crate) struct PackageManifest {
     pub name: String,
-    pub version: Version,
+    /// Version text, parsed separately to report version errors with the manifest path.
+    pub version: String,
+    /// Patterns of the files to include when packing the package.
+    #[serde(default)]
+    pub files: Option<Vec<String>>,
+    /// Versions of the packages the package depends on, keyed by name.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+    /// Minimum Unity version the package supports, as `major.minor`.
+    pub unity: Option<String>,
+    /// Minimum Unity release of the `unity` version the package supports, such as `0b4`.
+    #[serde(rename = "unityRelease")]
+    pub unity_release: Option<String>,
+}
+
+/// Reads the package manifest at `path`.
+fn read_package_manifest(path: &Path) -> Result<(String, Version)> {
+    parse_package_manifest(read_json(path)?, path)
+}
+
+/// Reads the package manifest of the tarball at `path`.
+fn read_tarball_manifest(path: &Path) -> Result<(String, Version)> {
+    parse_package_manifest(read_tarball_package_manifest(path)?, path)
+}
+
+/// Reads the unparsed package manifest of the tarball at `path`.
+fn read_tarball_package_manifest(path: &Path) -> Result<PackageManifest> {
+    let text = read_tarball_manifest_text(path)?;
+    serde_json::from_str(text.as_str()).map_err(Error::json(path, "parse"))
+}
+
+/// Returns the name and parsed version of `manifest` read from the file at `path`.
+pub(crate) fn parse_package_manifest(
+    manifest: PackageManifest,
+    path: &Path,
+) -> Result<(String, Version)> {
+    let version =
+        Version::from_str(manifest.version.as_str()).map_err(|source| Error::Version {
+            path: path.to_path_buf(),
+            value: manifest.version.clone(),
+            source,
+        })?;
+    Ok((manifest.name, version))
+}
+
+/// Kind of location a package was found in.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default, Debug)]
+pub enum PackageSource {
+    /// Package directory.
+    #[default]
+    Directory,
+    /// Package tarball created by `npm pack`.
+    Tarball,
+    /// Package directory inside a git submodule.
+    Submodule,
+}
+
+impl PackageSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            PackageSource::Directory => "directory",
+            PackageSource::Tarball => "tarball",
+            PackageSource::Submodule => "submodule",
+        }
+    }
+}
+
+impl fmt::Display for PackageSource {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.name())
+    }
 }
 
 /// Unity package metadata.
@@ -28,21 +130,86 @@ struct PackageManifest {
 pub struct Package {
     pub name: String,
     pub version: Version,
+    /// Path of the package directory, or of the archive for tarballs.
     pub path: PathBuf,
+    pub source: PackageSource,
+}
+
+impl Package {
+    /// Reads the versions of the packages the package depends on from its manifest, keyed by
+    /// name.
+    pub fn read_dependencies(&self) -> Result<BTreeMap<String, String>> {
+        Ok(self.read_manifest()?.dependencies)
+    }
+
+    /// Reads the unparsed manifest of the package.
+    pub(crate) fn read_manifest(&self) -> Result<PackageManifest> {
+        match self.source {
+            PackageSource::Tarball => read_tarball_package_manifest(self.path.as_path()),
+            _ => read_json(self.path.join(PACKAGE_MANIFEST_FILENAME)),
+        }
+    }
 }
 
 /// Iterator over UPM packages.
 pub struct PackageIterator {
-    it: DirEntryIter<((), ())>,
+    it: DirEntryIter<(WalkState, PackageSource)>,
 }
 
 impl PackageIterator {
+    /// Creates an iterator over the packages in `packages_path`, skipping the directories Unity
+    /// ignores.
     pub fn new<P: AsRef<Path>>(packages_path: P) -> PackageIterator {
-        let packages_path = fs::canonicalize(packages_path.as_ref())
-            .unwrap_or(packages_path.as_ref().to_path_buf());
+        let packages_path = canonicalize(packages_path.as_ref());
+        let filter = DirectoryFilter::unity(packages_path.as_path());
+        Self::with_filter(packages_path.as_path(), filter)
+    }
+
+    /// Creates an iterator over the packages in `packages_path`, additionally skipping the
+    /// directories excluded by `options`. Fails if an exclude pattern is invalid.
+    pub fn with_options<P: AsRef<Path>>(
+        packages_path: P,
+        options: &DiscoveryOptions,
+    ) -> Result<PackageIterator> {
+        let packages_path = canonicalize(packages_path.as_ref());
+        let filter = DirectoryFilter::new(packages_path.as_path(), options)?;
+        Ok(Self::with_filter(packages_path.as_path(), filter))
+    }
+
+    fn with_filter(packages_path: &Path, filter: DirectoryFilter) -> PackageIterator {
+        let filter = Arc::new(filter);
+        let root_state = WalkState {
+            gitignores: filter.get_ancestor_gitignores(),
+            in_submodule: false,
+        };
+
+        let walk_dir = WalkDirGeneric::<(WalkState, PackageSource)>::new(packages_path)
+            .root_read_dir_state(root_state)
+            // Hidden directories are skipped by the filter, but `.git` entries are needed to
+            // detect submodules.
+            .skip_hidden(false)
+            .process_read_dir(move |depth, path, state, children| {
+                // The root directory itself is always walked.
+                if depth.is_none() {
+                    return;
+                }
+
+                // Changes of the state are passed on to the child directories.
+                filter.push_gitignore(&mut state.gitignores, path);
+
+                if filter.is_submodule(path, children) {
+                    if !filter.include_submodules() {
+                        children.clear();
+                        return;
+                    }
+                    state.in_submodule = true;
+                }
+                if state.in_submodule {
+                    for dir_entry in children.iter_mut().flatten() {
+                        dir_entry.client_state = PackageSource::Submodule;
+                    }
+                }
 
-        let walk_dir = WalkDir::new(packages_path.as_path()).process_read_dir(
-            |_depth, _path, _read_dir_state, children| {
                 // Find index of package.json file in children.
                 let mut package_index = None;
                 for (index, dir_entry_result) in children.iter().enumerate() {
@@ -67,15 +234,22 @@ impl PackageIterator {
                         retain
                     });
                 } else {
+                    // Errors are kept so that they are reported by the checked iterator.
                     children.retain(|dir_entry_result| {
                         dir_entry_result
                             .as_ref()
-                            .map(|dir_entry| dir_entry.file_type.is_dir())
-                            .unwrap_or(false)
+                            .map(|dir_entry| {
+                                if dir_entry.file_type.is_file() {
+                                    return dir_entry.file_name.to_str().is_some_and(is_tarball);
+                                }
+                                dir_entry.file_type.is_dir()
+                                    && !filter
+                                        .is_skipped(dir_entry.path().as_path(), &state.gitignores)
+                            })
+                            .unwrap_or(true)
                     });
                 }
-            },
-        );
+            });
 
         PackageIterator {
             it: walk_dir.into_iter(),
@@ -83,33 +257,121 @@ impl PackageIterator {
     }
 }
 
-impl Iterator for PackageIterator {
-    type Item = Package;
+impl PackageIterator {
+    /// Returns an iterator that yields an error for each package that cannot be read instead of
+    /// skipping it.
+    pub fn checked(self) -> CheckedPackageIterator {
+        CheckedPackageIterator { packages: self }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        for entry in self.it.by_ref().flatten() {
+    fn next_result(&mut self) -> Option<result::Result<Package, DiscoveryError>> {
+        for entry_result in self.it.by_ref() {
+            let entry = match entry_result {
+                Ok(entry) => entry,
+                Err(error) => return Some(Err(DiscoveryError::Walk(error))),
+            };
             if !entry.file_type.is_file() {
                 continue;
             }
-            let Ok(package): Result<PackageManifest> = read_json(entry.path()) else {
-                continue;
+
+            let mut package_path = entry.path();
+            let is_tarball = entry.file_name.to_str().is_some_and(is_tarball);
+            let manifest_result = if is_tarball {
+                read_tarball_manifest(package_path.as_path())
+            } else {
+                read_package_manifest(package_path.as_path())
+            };
+            let (name, version) = match manifest_result {
+                Ok(manifest) => manifest,
+                Err(error) => return Some(Err(DiscoveryError::Manifest(error))),
             };
 
-            let mut package_path = entry.path().to_path_buf();
-            package_path.pop();
+            let source = if is_tarball {
+                PackageSource::Tarball
+            } else {
+                package_path.pop();
+                entry.client_state
+            };
 
-            return Some(Package {
-                name: package.name,
-                version: package.version,
+            return Some(Ok(Package {
+                name,
+                version,
                 path: package_path,
-            });
+                source,
+            }));
+        }
+        None
+    }
+}
+
+impl Iterator for PackageIterator {
+    type Item = Package;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(package_result) = self.next_result() {
+            if let Ok(package) = package_result {
+                return Some(package);
+            }
         }
         None
     }
 }
 
+/// Iterator over UPM packages that also yields the packages that cannot be read as errors.
+pub struct CheckedPackageIterator {
+    packages: PackageIterator,
+}
+
+impl Iterator for CheckedPackageIterator {
+    type Item = result::Result<Package, DiscoveryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.packages.next_result()
+    }
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or(path.to_path_buf())
+}
+
 /// Recursively scans the provided `packages_path` for UPM packages and returns iterator over found
 /// packages.
 pub fn find_packages<P: AsRef<Path>>(packages_path: P) -> PackageIterator {
     PackageIterator::new(packages_path)
 }
+
+/// Recursively scans the provided `packages_path` for UPM packages, skipping the directories
+/// excluded by `options`, and returns iterator over found packages.
+pub fn find_packages_with_options<P: AsRef<Path>>(
+    packages_path: P,
+    options: &DiscoveryOptions,
+) -> Result<PackageIterator> {
+    PackageIterator::with_options(packages_path, options)
+}
+
+/// Finds the packages in `packages_path`, skipping the directories excluded by `options`, and
+/// calls `warn` for each package that cannot be read. Fails if there are such packages and
+/// `strict` is set.
+pub fn find_valid_packages<P: AsRef<Path>>(
+    packages_path: P,
+    options: &DiscoveryOptions,
+    strict: bool,
+    mut warn: impl FnMut(&DiscoveryError),
+) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    let mut count = 0;
+    for package_result in find_packages_with_options(packages_path, options)?.checked() {
+        match package_result {
+            Ok(package) => packages.push(package),
+            Err(error) => {
+                warn(&error);
+                count += 1;
+            }
+        }
+    }
+
+    if strict && count > 0 {
+        return Err(Error::InvalidPackages { count });
+    }
+    Ok(packages)
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::manifest::find_referenced_package;
+use crate::{
+    Manifest, Package, RegistryVersion, Result, UNITY_PACKAGE_PREFIX, UnityVersion, canonicalize,
+    get_file_reference,
+};
+
+/// Problem found in a project manifest and its local packages.
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum LintIssue {
+    /// A local package requires a higher version of a dependency than the manifest pins.
+    DependencyBelowRequirement {
+        package: String,
+        dependency: String,
+        required: String,
+        pinned: String,
+    },
+    /// A local package requires a higher Unity version than the project's.
+    UnityVersionTooLow {
+        package: String,
+        required: String,
+        project: String,
+    },
+    /// A dependency of a local package, or of the manifest if `package` is `None`, can be found
+    /// neither locally nor in a registry.
+    MissingDependency {
+        package: Option<String>,
+        dependency: String,
+        version: String,
+    },
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LintIssue::DependencyBelowRequirement {
+                package,
+                dependency,
+                required,
+                pinned,
+            } => write!(
+                formatter,
+                "{} requires {} {}, but the manifest pins {}",
+                package, dependency, required, pinned
+            ),
+            LintIssue::UnityVersionTooLow {
+                package,
+                required,
+                project,
+            } => write!(
+                formatter,
+                "{} requires Unity {}, but the project uses {}",
+                package, required, project
+            ),
+            LintIssue::MissingDependency {
+                package,
+                dependency,
+                version,
+            } => write!(
+                formatter,
+                "{} depends on missing package {} {}",
+                package.as_deref().unwrap_or("the manifest"),
+                dependency,
+                version
+            ),
+        }
+    }
+}
+
+/// Returns the version the manifest dependency `value` pins in the manifest directory
+/// `manifest_dir`: the version itself, or that of the local package a `file:` reference points
+/// at.
+fn get_pinned_version(
+    value: &str,
+    manifest_dir: &Path,
+    packages: &[Package],
+) -> Option<RegistryVersion> {
+    match get_file_reference(value) {
+        Some(_) => find_referenced_package(value, manifest_dir, packages)
+            .map(|package| package.version.into()),
+        None => RegistryVersion::from_str(value).ok(),
+    }
+}
+
+/// Checks the manifest `manifest` in the directory `manifest_dir` and the local `packages` for
+/// dependencies pinned below the versions the packages require, packages that require a higher
+/// Unity version than `project_unity_version`, if given, and dependencies that are missing. A
+/// dependency is missing if it is neither a local package Unity loads, which is one embedded in
+/// the manifest directory or referenced with `file:`, a manifest dependency, a Unity package nor
+/// in the scope of a scoped registry, or if it is a `file:` reference to a path that does not
+/// exist.
+pub fn lint_project(
+    manifest: &Manifest,
+    manifest_dir: &Path,
+    packages: &[Package],
+    project_unity_version: Option<&UnityVersion>,
+) -> Result<Vec<LintIssue>> {
+    let manifest_dir = canonicalize(manifest_dir);
+    let dependencies = manifest.dependencies.clone().unwrap_or_default();
+    let package_names = packages
+        .iter()
+        .filter(|package| package.path.parent() == Some(manifest_dir.as_path()))
+        .chain(
+            dependencies
+                .values()
+                .filter_map(|value| find_referenced_package(value, &manifest_dir, packages)),
+        )
+        .map(|package| package.name.as_str())
+        .collect::<HashSet<_>>();
+
+    let mut issues = Vec::new();
+    for (name, value) in dependencies.iter() {
+        if let Some(path) = get_file_reference(value)
+            && !manifest_dir.join(path).exists()
+        {
+            issues.push(LintIssue::MissingDependency {
+                package: None,
+                dependency: name.clone(),
+                version: value.clone(),
+            });
+        }
+    }
+
+    let mut sorted_packages = packages.iter().collect::<Vec<_>>();
+    sorted_packages.sort_unstable_by_key(|package| package.name.as_str());
+    for package in sorted_packages {
+        let package_manifest = package.read_manifest()?;
+
+        for (dependency, version) in package_manifest.dependencies.iter() {
+            match dependencies.get(dependency) {
+                Some(value) => {
+                    let Ok(required) = RegistryVersion::from_str(version) else {
+                        continue;
+                    };
+                    let Some(pinned) = get_pinned_version(value, &manifest_dir, packages) else {
+                        continue;
+                    };
+                    if required > pinned {
+                        issues.push(LintIssue::DependencyBelowRequirement {
+                            package: package.name.clone(),
+                            dependency: dependency.clone(),
+                            required: version.clone(),
+                            pinned: pinned.to_string(),
+                        });
+                    }
+                }
+                None => {
+                    if package_names.contains(dependency.as_str())
+                        || dependency.starts_with(UNITY_PACKAGE_PREFIX)
+                        || manifest.get_scoped_registry(dependency).is_some()
+                    {
+                        continue;
+                    }
+                    issues.push(LintIssue::MissingDependency {
+                        package: Some(package.name.clone()),
+                        dependency: dependency.clone(),
+                        version: version.clone(),
+                    });
+                }
+            }
+        }
+
+        let Some(project) = project_unity_version else {
+            continue;
+        };
+        let required = package_manifest.unity.as_deref().and_then(|unity| {
+            UnityVersion::from_package_fields(unity, package_manifest.unity_release.as_deref()).ok()
+        });
+        if let Some(required) = required
+            && required > *project
+        {
+            issues.push(LintIssue::UnityVersionTooLow {
+                package: package.name.clone(),
+                required: required.to_string(),
+                project: project.to_string(),
+            });
+        }
+    }
+    Ok(issues)
+}
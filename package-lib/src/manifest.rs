@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Package, canonicalize};
+
+/// Prefix of manifest dependencies that reference local packages by path.
+pub const FILE_PREFIX: &str = "file:";
+
+/// Prefix of the packages served by Unity's own registry, which needs no scoped registry.
+pub const UNITY_PACKAGE_PREFIX: &str = "com.unity.";
+
+/// Returns the path the manifest dependency `value` references if it is a `file:` reference.
+pub fn get_file_reference(value: &str) -> Option<&str> {
+    value.strip_prefix(FILE_PREFIX)
+}
+
+/// Returns the directory of the project manifest at `manifest_path`, which is `.` for a bare
+/// filename.
+pub fn get_manifest_dir(manifest_path: &Path) -> &Path {
+    manifest_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// Returns the package of `packages` that the `file:` reference `value` in the canonical manifest
+/// directory `manifest_dir` points at, if it is one and the package was found.
+pub(crate) fn find_referenced_package<'a>(
+    value: &str,
+    manifest_dir: &Path,
+    packages: &'a [Package],
+) -> Option<&'a Package> {
+    let path = canonicalize(manifest_dir.join(get_file_reference(value)?).as_path());
+    packages.iter().find(|package| package.path == path)
+}
+
+/// Returns whether the scope `scope` matches the package named `package_name`, which it does if
+/// the name equals it or continues it with `.` or `/`.
+pub fn scope_matches(scope: &str, package_name: &str) -> bool {
+    package_name
+        .strip_prefix(scope)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '/']))
+}
+
+/// Registry that Unity resolves the packages of `scopes` from.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScopedRegistry {
+    pub name: String,
+    pub url: String,
+    pub scopes: Vec<String>,
+}
+
+impl ScopedRegistry {
+    /// Returns the length of the longest scope of the registry that matches the package named
+    /// `package_name`, if any matches.
+    pub fn get_match_length(&self, package_name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .filter(|scope| scope_matches(scope, package_name))
+            .map(|scope| scope.len())
+            .max()
+    }
+}
+
+/// Project manifest of a Unity project, `Packages/manifest.json`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<BTreeMap<String, String>>,
+    #[serde(rename = "enableLockFile")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_lock_file: Option<bool>,
+    #[serde(rename = "resolutionStrategy")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution_strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "scopedRegistries")]
+    pub scoped_registries: Option<Vec<ScopedRegistry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub testables: Option<Vec<String>>,
+}
+
+impl Manifest {
+    /// Returns the scoped registry the package named `package_name` is resolved from, which is
+    /// the one with the longest matching scope, like Unity picks it.
+    pub fn get_scoped_registry(&self, package_name: &str) -> Option<&ScopedRegistry> {
+        self.scoped_registries
+            .iter()
+            .flatten()
+            .filter_map(|registry| {
+                registry
+                    .get_match_length(package_name)
+                    .map(|length| (length, registry))
+            })
+            .max_by_key(|(length, _)| *length)
+            .map(|(_, registry)| registry)
+    }
+}
@@ -0,0 +1,313 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use globset::Glob;
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use tar::{Builder, Header};
+
+use crate::{
+    Error, PACKAGE_MANIFEST_FILENAME, PackageManifest, Result, Version, parse_package_manifest,
+    read_json,
+};
+
+/// Directory all files are stored in inside package tarballs.
+const TARBALL_ROOT: &str = "package";
+
+/// Modification time of all tarball entries, 1985-10-26T08:15:00Z, which is what `npm pack` uses
+/// to make tarballs reproducible.
+const TARBALL_MTIME: u64 = 499_162_500;
+
+/// Ignore files read in each directory. The first one found is used.
+const IGNORE_FILENAMES: [&str; 2] = [".npmignore", ".gitignore"];
+
+/// Files and directories `npm pack` never includes, in `.gitignore` syntax.
+const DEFAULT_IGNORE_RULES: [&str; 19] = [
+    ".npmignore",
+    ".gitignore",
+    ".git",
+    ".svn",
+    ".hg",
+    "CVS",
+    ".npmrc",
+    ".lock-wscript",
+    ".wafpickle-*",
+    ".*.swp",
+    ".DS_Store",
+    "._*",
+    "*.orig",
+    "npm-debug.log",
+    "node_modules/",
+    "/package-lock.json",
+    "/yarn.lock",
+    "/pnpm-lock.yaml",
+    "/archived-packages/",
+];
+
+/// Name prefixes of files in the package root that are always included, compared ignoring case.
+const ALWAYS_INCLUDED_PREFIXES: [&str; 4] = ["readme", "license", "licence", "copying"];
+
+/// Name, version and files of a package directory as `npm pack` would pack it.
+#[derive(Debug)]
+pub struct PackContents {
+    pub name: String,
+    pub version: Version,
+    /// Paths of the packed files relative to the package directory, sorted.
+    pub files: Vec<PathBuf>,
+}
+
+impl PackContents {
+    /// Returns the filename `npm pack` gives the tarball, such as `com.company.tools-1.0.0.tgz`
+    /// or `scope-name-1.0.0.tgz` for `@scope/name`.
+    pub fn tarball_filename(&self) -> String {
+        let name = self.name.strip_prefix('@').unwrap_or(&self.name);
+        format!("{}-{}.tgz", name.replace('/', "-"), self.version)
+    }
+}
+
+/// Returns whether the file at `relative_path` is always packed, regardless of ignore files and
+/// the `files` field.
+fn is_always_included(relative_path: &Path) -> bool {
+    if relative_path.parent() != Some(Path::new("")) {
+        return false;
+    }
+    let Some(name) = relative_path.to_str() else {
+        return false;
+    };
+    if name == PACKAGE_MANIFEST_FILENAME {
+        return true;
+    }
+
+    let name = name.to_ascii_lowercase();
+    ALWAYS_INCLUDED_PREFIXES.iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// Returns the matcher of the files `npm pack` never includes.
+fn get_default_ignore(package_path: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(package_path);
+    for rule in DEFAULT_IGNORE_RULES {
+        // The rules are known to be valid.
+        let _ = builder.add_line(None, rule);
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Returns the matcher of the `files` field of the manifest, whose patterns are relative to the
+/// package directory and may be negated with `!`.
+fn get_files_matcher(package_path: &Path, files: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(package_path);
+    for pattern in files {
+        let (negation, glob) = match pattern.strip_prefix('!') {
+            Some(glob) => ("!", glob),
+            None => ("", pattern.as_str()),
+        };
+        let glob = glob.trim_start_matches("./").trim_start_matches('/');
+        Glob::new(glob).map_err(|source| Error::Pattern {
+            pattern: pattern.clone(),
+            source,
+        })?;
+        let _ = builder.add_line(None, format!("{}/{}", negation, glob).as_str());
+    }
+    Ok(builder.build().unwrap_or_else(|_| Gitignore::empty()))
+}
+
+/// Returns the matcher of the first ignore file in `dir`, if there is one.
+fn get_ignore_file(dir: &Path) -> Option<Gitignore> {
+    IGNORE_FILENAMES.iter().find_map(|filename| {
+        let path = dir.join(filename);
+        if !path.is_file() {
+            return None;
+        }
+        // Invalid lines are skipped, like npm does.
+        let (gitignore, _error) = Gitignore::new(path);
+        Some(gitignore)
+    })
+}
+
+/// Selects the files of a package directory.
+struct FileSelector<'a> {
+    package_path: &'a Path,
+    default_ignore: Gitignore,
+    files: Option<Gitignore>,
+}
+
+impl FileSelector<'_> {
+    /// Adds the paths of the selected files in the directory `relative_dir` to `selected`.
+    /// `ignores` are the matchers of the ignore files of the parent directories.
+    fn collect(
+        &self,
+        relative_dir: &Path,
+        ignores: &mut Vec<Gitignore>,
+        selected: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        let dir = self.package_path.join(relative_dir);
+        // With a `files` field, the ignore file in the package root does not apply.
+        let ignore = if self.files.is_some() && relative_dir.as_os_str().is_empty() {
+            None
+        } else {
+            get_ignore_file(dir.as_path())
+        };
+        let pushed = ignore.is_some();
+        ignores.extend(ignore);
+
+        let mut entries = fs::read_dir(dir.as_path())
+            .and_then(|entries| entries.collect::<std::io::Result<Vec<_>>>())
+            .map_err(Error::io(dir.as_path(), "read directory"))?;
+        entries.sort_unstable_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(Error::io(path.as_path(), "read"))?;
+            // Symbolic links are not packed, like npm does.
+            if !file_type.is_dir() && !file_type.is_file() {
+                continue;
+            }
+            let is_dir = file_type.is_dir();
+            let relative_path = relative_dir.join(entry.file_name());
+
+            if !is_dir && is_always_included(relative_path.as_path()) {
+                selected.push(relative_path);
+                continue;
+            }
+            if self.is_ignored(path.as_path(), is_dir, ignores) {
+                continue;
+            }
+
+            if is_dir {
+                self.collect(relative_path.as_path(), ignores, selected)?;
+            } else if self.files.as_ref().is_none_or(|files| {
+                files
+                    .matched_path_or_any_parents(path.as_path(), false)
+                    .is_ignore()
+            }) {
+                selected.push(relative_path);
+            }
+        }
+
+        if pushed {
+            ignores.pop();
+        }
+        Ok(())
+    }
+
+    fn is_ignored(&self, path: &Path, is_dir: bool, ignores: &[Gitignore]) -> bool {
+        if self.default_ignore.matched(path, is_dir).is_ignore() {
+            return true;
+        }
+        for ignore in ignores.iter().rev() {
+            match ignore.matched(path, is_dir) {
+                Match::None => continue,
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+            }
+        }
+        false
+    }
+}
+
+/// Reads the manifest of the package directory at `package_path` and selects the files
+/// `npm pack` would include: all files except the ones excluded by `.npmignore` files, or
+/// `.gitignore` files in directories without one, and the files npm never includes. If the
+/// manifest has a `files` field, only the matching files are included. The manifest, readme and
+/// license files are always included. Directories ending in `~` are only ignored by Unity on
+/// import and are included.
+pub fn get_pack_contents<P: AsRef<Path>>(package_path: P) -> Result<PackContents> {
+    let package_path = package_path.as_ref();
+    let manifest_path = package_path.join(PACKAGE_MANIFEST_FILENAME);
+    let mut manifest: PackageManifest = read_json(manifest_path.as_path())?;
+    let files = manifest.files.take();
+    let (name, version) = parse_package_manifest(manifest, manifest_path.as_path())?;
+
+    let selector = FileSelector {
+        package_path,
+        default_ignore: get_default_ignore(package_path),
+        files: files
+            .map(|files| get_files_matcher(package_path, files.as_slice()))
+            .transpose()?,
+    };
+    let mut selected = Vec::new();
+    selector.collect(Path::new(""), &mut Vec::new(), &mut selected)?;
+    selected.sort_unstable();
+
+    Ok(PackContents {
+        name,
+        version,
+        files: selected,
+    })
+}
+
+/// Writes a gzipped tarball of the `contents` of the package directory at `package_path` to
+/// `writer` and returns the writer. The tarball is reproducible: the entries are sorted, stored
+/// under `package/` and have a fixed modification time, owner and mode.
+pub fn write_package_tarball<P, W>(package_path: P, contents: &PackContents, writer: W) -> Result<W>
+where
+    P: AsRef<Path>,
+    W: Write,
+{
+    let package_path = package_path.as_ref();
+    let mut builder = Builder::new(GzEncoder::new(writer, Compression::best()));
+
+    for relative_path in contents.files.iter() {
+        let path = package_path.join(relative_path);
+        let data = fs::read(path.as_path()).map_err(Error::io(path.as_path(), "read"))?;
+
+        let mut header = Header::new_ustar();
+        header.set_size(data.len() as u64);
+        header.set_mode(get_file_mode(path.as_path()));
+        header.set_mtime(TARBALL_MTIME);
+        header.set_uid(0);
+        header.set_gid(0);
+        let tarball_path = Path::new(TARBALL_ROOT).join(relative_path);
+        if let Err(error) = builder.append_data(&mut header, tarball_path, data.as_slice()) {
+            return Err(Error::io(package_path, "pack")(error));
+        }
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(Error::io(package_path, "pack"))
+}
+
+/// Returns the normalized mode of the file at `path`, 0o755 if it is executable and 0o644
+/// otherwise.
+#[cfg(unix)]
+fn get_file_mode(path: &Path) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    let is_executable = fs::metadata(path)
+        .map(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false);
+    if is_executable { 0o755 } else { 0o644 }
+}
+
+#[cfg(not(unix))]
+fn get_file_mode(_path: &Path) -> u32 {
+    0o644
+}
+
+/// Packs the package directory at `package_path` into a tarball like `npm pack` does and writes
+/// it to `output_dir`. Returns the packed contents and the path of the tarball.
+pub fn pack_package<P, O>(package_path: P, output_dir: O) -> Result<(PackContents, PathBuf)>
+where
+    P: AsRef<Path>,
+    O: AsRef<Path>,
+{
+    let package_path = package_path.as_ref();
+    let contents = get_pack_contents(package_path)?;
+    let tarball_path = output_dir.as_ref().join(contents.tarball_filename());
+
+    let file = File::create(tarball_path.as_path()).map_err(Error::io(&tarball_path, "create"))?;
+    write_package_tarball(package_path, &contents, BufWriter::new(file))?
+        .flush()
+        .map_err(Error::io(&tarball_path, "write"))?;
+    Ok((contents, tarball_path))
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use sha1::Sha1;
+use sha2::{Digest, Sha512};
+use ureq::Agent;
+
+use crate::{Error, PACKAGE_MANIFEST_FILENAME, PackContents, Result, read_json};
+
+/// Filename of npm configuration files.
+const NPMRC_FILENAME: &str = ".npmrc";
+
+/// Suffix of the `.npmrc` keys of registry tokens, which are prefixed by the registry URL
+/// without the protocol, such as `//registry.example.com/:_authToken`.
+const TOKEN_KEY_SUFFIX: &str = ":_authToken";
+
+/// Returns the `.npmrc` files npm reads the registry token from: the one in the current directory
+/// and the one in the home directory.
+pub fn get_npmrc_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(NPMRC_FILENAME)];
+    if let Some(home) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        paths.push(Path::new(&home).join(NPMRC_FILENAME));
+    }
+    paths
+}
+
+/// Returns `url` without the protocol and with a trailing slash, as registries are identified in
+/// `.npmrc` files. Keys that are already in this form, which may contain a port, are kept.
+fn get_npmrc_registry_key(url: &str) -> String {
+    let url = url.find("://").map_or(url, |index| &url[index + 1..]);
+    let mut key = url.to_owned();
+    if !key.ends_with('/') {
+        key.push('/');
+    }
+    key
+}
+
+/// Replaces `${NAME}` references to environment variables in an `.npmrc` value.
+fn expand_variables(value: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(
+            env::var(&rest[start + 2..start + length])
+                .unwrap_or_default()
+                .as_str(),
+        );
+        rest = &rest[start + length + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Returns the token of the registry at `url` from the `.npmrc` text `npmrc`. The token of the
+/// longest registry path that the URL starts with is used, like npm does.
+fn find_npmrc_token(npmrc: &str, url: &str) -> Option<String> {
+    let registry_key = get_npmrc_registry_key(url);
+    npmrc
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let prefix = key.trim().strip_suffix(TOKEN_KEY_SUFFIX)?;
+            let prefix = get_npmrc_registry_key(prefix);
+            registry_key
+                .starts_with(prefix.as_str())
+                .then_some((prefix.len(), value))
+        })
+        .max_by_key(|(length, _)| *length)
+        .map(|(_, value)| expand_variables(value.trim().trim_matches('"')))
+        .filter(|token| !token.is_empty())
+}
+
+/// Returns the token to authenticate with at the registry at `url` from the first of the `.npmrc`
+/// files at `npmrc_paths` that has one for it. Missing files are skipped. Tokens are only read
+/// from keys of the registry, such as `//registry.example.com/:_authToken=${NPM_TOKEN}`, so that
+/// they are never sent to other registries.
+pub fn find_registry_token(url: &str, npmrc_paths: &[PathBuf]) -> Option<String> {
+    npmrc_paths.iter().find_map(|path| {
+        let npmrc = fs::read_to_string(path).ok()?;
+        find_npmrc_token(npmrc.as_str(), url)
+    })
+}
+
+/// Package document of a registry, with the published versions.
+#[derive(Deserialize, Debug)]
+struct PackageDocument {
+    #[serde(default)]
+    versions: BTreeMap<String, Value>,
+}
+
+/// Returns the lowercase hexadecimal text of `bytes`.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Client of a registry that speaks the npm protocol, such as Verdaccio.
+pub struct Registry {
+    /// URL without a trailing slash.
+    url: String,
+    token: Option<String>,
+    agent: Agent,
+}
+
+impl Registry {
+    pub fn new(url: &str, token: Option<String>) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_owned(),
+            token,
+            agent: Agent::new_with_defaults(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// Returns the URL of the document of the package named `name`. The slash of scoped names
+    /// is escaped, like npm does.
+    fn get_package_url(&self, name: &str) -> String {
+        format!("{}/{}", self.url, name.replace('/', "%2f"))
+    }
+
+    fn get_authorization(&self) -> Option<String> {
+        self.token.as_ref().map(|token| format!("Bearer {}", token))
+    }
+
+    /// Returns the versions of the package named `name` published to the registry, or no
+    /// versions if the registry does not know the package.
+    pub fn get_versions(&self, name: &str) -> Result<Vec<String>> {
+        let url = self.get_package_url(name);
+        let mut request = self
+            .agent
+            .get(url.as_str())
+            .header("Accept", "application/json");
+        if let Some(authorization) = self.get_authorization() {
+            request = request.header("Authorization", authorization);
+        }
+
+        let text = match request
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string())
+        {
+            Ok(text) => text,
+            Err(ureq::Error::StatusCode(404)) => return Ok(Vec::new()),
+            Err(source) => return Err(Error::registry(url.as_str(), "query")(source)),
+        };
+        let document: PackageDocument = serde_json::from_str(text.as_str()).map_err(|error| {
+            Error::registry(url.as_str(), "query")(ureq::Error::Other(error.into()))
+        })?;
+        Ok(document.versions.into_keys().collect())
+    }
+
+    /// Publishes the version of the package directory at `package_path` with the packed
+    /// `contents` and the gzipped `tarball` of them.
+    pub fn publish<P: AsRef<Path>>(
+        &self,
+        package_path: P,
+        contents: &PackContents,
+        tarball: &[u8],
+    ) -> Result<()> {
+        let manifest_path = package_path.as_ref().join(PACKAGE_MANIFEST_FILENAME);
+        let manifest: Map<String, Value> = read_json(manifest_path.as_path())?;
+        let document = self.get_publish_document(manifest, contents, tarball);
+
+        let url = self.get_package_url(contents.name.as_str());
+        let mut request = self.agent.put(url.as_str());
+        if let Some(authorization) = self.get_authorization() {
+            request = request.header("Authorization", authorization);
+        }
+        request
+            .content_type("application/json")
+            .send(document.to_string())
+            .map_err(Error::registry(url.as_str(), "publish to"))?;
+        Ok(())
+    }
+
+    /// Returns the document npm sends to publish a version: the package manifest with the
+    /// checksums and URL of the tarball, and the tarball itself as attachment.
+    fn get_publish_document(
+        &self,
+        mut manifest: Map<String, Value>,
+        contents: &PackContents,
+        tarball: &[u8],
+    ) -> Value {
+        let name = contents.name.as_str();
+        let version = contents.version.to_string();
+        let filename = contents.tarball_filename();
+        let tarball_url = format!("{}/{}/-/{}", self.url, name, filename);
+
+        manifest.insert(String::from("_id"), json!(format!("{}@{}", name, version)));
+        manifest.insert(
+            String::from("dist"),
+            json!({
+                "shasum": to_hex(Sha1::digest(tarball).as_slice()),
+                "integrity": format!("sha512-{}", BASE64.encode(Sha512::digest(tarball))),
+                "tarball": tarball_url,
+            }),
+        );
+
+        json!({
+            "_id": name,
+            "name": name,
+            "dist-tags": { "latest": version },
+            "versions": { version.as_str(): manifest },
+            "_attachments": {
+                filename: {
+                    "content_type": "application/octet-stream",
+                    "data": BASE64.encode(tarball),
+                    "length": tarball.len(),
+                },
+            },
+        })
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::result;
+use std::str::FromStr;
+
+use crate::manifest::find_referenced_package;
+use crate::{Manifest, Package, RegistryVersion, Result, canonicalize, get_file_reference};
+
+/// Strategy Unity uses to choose the versions of indirect dependencies, the `resolutionStrategy`
+/// of the project manifest.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub enum ResolutionStrategy {
+    /// Highest version requested by the dependents, which is the lowest that satisfies them all.
+    #[default]
+    Lowest,
+    /// Highest available version with the same major and minor version as the lowest.
+    HighestPatch,
+    /// Highest available version with the same major version as the lowest.
+    HighestMinor,
+    /// Highest available version.
+    Highest,
+}
+
+impl ResolutionStrategy {
+    /// Returns the name of the strategy in project manifests.
+    pub fn name(self) -> &'static str {
+        match self {
+            ResolutionStrategy::Lowest => "lowest",
+            ResolutionStrategy::HighestPatch => "highestPatch",
+            ResolutionStrategy::HighestMinor => "highestMinor",
+            ResolutionStrategy::Highest => "highest",
+        }
+    }
+
+    /// Returns whether the strategy may choose `candidate` when `lowest` satisfies all
+    /// dependents.
+    fn allows(self, lowest: &RegistryVersion, candidate: &RegistryVersion) -> bool {
+        let (lowest, candidate) = (&lowest.version, &candidate.version);
+        match self {
+            ResolutionStrategy::Lowest => false,
+            ResolutionStrategy::HighestPatch => {
+                candidate.major == lowest.major && candidate.minor == lowest.minor
+            }
+            ResolutionStrategy::HighestMinor => candidate.major == lowest.major,
+            ResolutionStrategy::Highest => true,
+        }
+    }
+}
+
+impl FromStr for ResolutionStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        [
+            ResolutionStrategy::Lowest,
+            ResolutionStrategy::HighestPatch,
+            ResolutionStrategy::HighestMinor,
+            ResolutionStrategy::Highest,
+        ]
+        .into_iter()
+        .find(|strategy| strategy.name() == value)
+        .ok_or_else(|| format!("unknown resolution strategy '{}'", value))
+    }
+}
+
+impl fmt::Display for ResolutionStrategy {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.name())
+    }
+}
+
+/// Why a package resolved to its version.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ResolutionReason {
+    /// The package is embedded in the project's packages directory, which always wins.
+    Embedded,
+    /// The manifest references the local package with a `file:` dependency.
+    Local,
+    /// The manifest depends on the package directly, which wins over all other requirements.
+    Direct,
+    /// The version was chosen from the requirements of the dependents with the strategy.
+    Requirements(ResolutionStrategy),
+}
+
+impl fmt::Display for ResolutionReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolutionReason::Embedded => write!(formatter, "embedded package"),
+            ResolutionReason::Local => write!(formatter, "local package"),
+            ResolutionReason::Direct => write!(formatter, "direct dependency"),
+            ResolutionReason::Requirements(strategy) => {
+                write!(formatter, "resolved with strategy {}", strategy)
+            }
+        }
+    }
+}
+
+/// Version of a package that another package depends on.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Requirement {
+    /// Packages from the direct dependency of the manifest to the dependent package, as
+    /// `name@version`. The last one requires the version.
+    pub chain: Vec<String>,
+    pub version: String,
+}
+
+/// Version Unity resolves a package of the project to.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ResolvedPackage {
+    pub name: String,
+    /// Resolved version, or the `file:` reference or URL the manifest depends on.
+    pub version: String,
+    pub reason: ResolutionReason,
+    /// Requirements of the packages that depend on this package.
+    pub requirements: Vec<Requirement>,
+}
+
+/// Returns the version `strategy` chooses for the versions `requested` by the dependents out of
+/// the `available` versions. Pre-releases are only chosen if they are requested.
+fn choose_version(
+    requested: &[&str],
+    available: &[RegistryVersion],
+    strategy: ResolutionStrategy,
+) -> String {
+    let Some(lowest) = requested
+        .iter()
+        .filter_map(|version| RegistryVersion::from_str(version).ok())
+        .max()
+    else {
+        return requested.first().copied().unwrap_or_default().to_owned();
+    };
+
+    available
+        .iter()
+        .filter(|version| {
+            **version > lowest && !version.is_pre_release() && strategy.allows(&lowest, version)
+        })
+        .max()
+        .unwrap_or(&lowest)
+        .to_string()
+}
+
+/// Package whose version is fixed by the project, with its version, reason and local package.
+type FixedPackage<'a> = (String, ResolutionReason, Option<&'a Package>);
+
+/// Shortest chains of dependents and requirements of the packages of a dependency graph.
+struct DependencyGraph {
+    /// Dependent of each package on its shortest chain from the manifest, or `None` for packages
+    /// fixed by the project.
+    parents: HashMap<String, Option<String>>,
+    /// Dependents of each package along with the version they require.
+    requirements: HashMap<String, Vec<(String, String)>>,
+}
+
+/// Walks the dependency graph breadth first from the `fixed` packages to find the shortest chain
+/// to each package. The dependencies of indirect dependencies are read from their local packages,
+/// except for those in `ignored`.
+fn walk_dependencies(
+    fixed: &BTreeMap<&str, FixedPackage>,
+    local_packages: &HashMap<&str, &Package>,
+    ignored: &HashSet<String>,
+) -> Result<DependencyGraph> {
+    let mut parents = HashMap::<String, Option<String>>::new();
+    let mut requirements = HashMap::<String, Vec<(String, String)>>::new();
+    let mut queue = VecDeque::new();
+    for name in fixed.keys() {
+        parents.insert(name.to_string(), None);
+        queue.push_back(name.to_string());
+    }
+    while let Some(name) = queue.pop_front() {
+        let package = match fixed.get(name.as_str()) {
+            Some((_, _, package)) => *package,
+            None if ignored.contains(&name) => None,
+            None => local_packages.get(name.as_str()).copied(),
+        };
+        let Some(package) = package else {
+            continue;
+        };
+        for (dependency, version) in package.read_dependencies()? {
+            requirements
+                .entry(dependency.clone())
+                .or_default()
+                .push((name.clone(), version));
+            if !parents.contains_key(&dependency) {
+                parents.insert(dependency.clone(), Some(name.clone()));
+                queue.push_back(dependency);
+            }
+        }
+    }
+    Ok(DependencyGraph {
+        parents,
+        requirements,
+    })
+}
+
+/// Simulates how Unity resolves the packages of the project with the manifest `manifest` in the
+/// directory `manifest_dir`, given the local `packages` and the versions available in registries
+/// by package name. Packages embedded in the manifest directory win, then `file:` and other
+/// direct dependencies of the manifest. The versions of indirect dependencies are chosen from the
+/// requirements of their dependents with `strategy`. The dependencies of packages are read from
+/// the local packages of the resolved versions, so those of packages without such a local package
+/// are unknown. Returns the resolved packages sorted by name.
+pub fn resolve_dependencies(
+    manifest: &Manifest,
+    manifest_dir: &Path,
+    packages: &[Package],
+    available_versions: &HashMap<String, Vec<RegistryVersion>>,
+    strategy: ResolutionStrategy,
+) -> Result<Vec<ResolvedPackage>> {
+    let manifest_dir = canonicalize(manifest_dir);
+    let local_packages = packages
+        .iter()
+        .map(|package| (package.name.as_str(), package))
+        .collect::<HashMap<_, _>>();
+
+    let mut fixed = BTreeMap::<&str, FixedPackage>::new();
+    for package in packages.iter() {
+        if package.path.parent() == Some(manifest_dir.as_path()) {
+            let version = package.version.to_string();
+            let resolved = (version, ResolutionReason::Embedded, Some(package));
+            fixed.insert(package.name.as_str(), resolved);
+        }
+    }
+    for (name, value) in manifest.dependencies.iter().flatten() {
+        if fixed.contains_key(name.as_str()) {
+            continue;
+        }
+        let resolved = match get_file_reference(value) {
+            Some(_) => {
+                let package = find_referenced_package(value, &manifest_dir, packages);
+                let version = package.map_or(value.clone(), |package| package.version.to_string());
+                (version, ResolutionReason::Local, package)
+            }
+            None => {
+                let package = local_packages
+                    .get(name.as_str())
+                    .copied()
+                    .filter(|package| package.version.to_string() == *value);
+                (value.clone(), ResolutionReason::Direct, package)
+            }
+        };
+        fixed.insert(name.as_str(), resolved);
+    }
+
+    // Indirect dependencies whose local package is another version than the one they resolve to.
+    // Their dependencies are left out and the versions chosen again until none are left, which
+    // ends as the set only grows.
+    let mut ignored = HashSet::<String>::new();
+    let (parents, requirements, versions) = loop {
+        let DependencyGraph {
+            parents,
+            requirements,
+        } = walk_dependencies(&fixed, &local_packages, &ignored)?;
+
+        let mut versions = HashMap::<String, (String, ResolutionReason)>::new();
+        for name in parents.keys() {
+            let resolved = match fixed.get(name.as_str()) {
+                Some((version, reason, _)) => (version.clone(), *reason),
+                None => {
+                    let requested = requirements
+                        .get(name)
+                        .into_iter()
+                        .flatten()
+                        .map(|(_, version)| version.as_str())
+                        .collect::<Vec<_>>();
+                    let mut available = available_versions.get(name).cloned().unwrap_or_default();
+                    if let Some(package) = local_packages.get(name.as_str()) {
+                        available.push(package.version.into());
+                    }
+                    let version =
+                        choose_version(requested.as_slice(), available.as_slice(), strategy);
+                    (version, ResolutionReason::Requirements(strategy))
+                }
+            };
+            versions.insert(name.clone(), resolved);
+        }
+
+        let mismatched = versions
+            .iter()
+            .filter(|(name, (version, _))| {
+                !fixed.contains_key(name.as_str())
+                    && !ignored.contains(*name)
+                    && local_packages
+                        .get(name.as_str())
+                        .is_some_and(|package| package.version.to_string() != *version)
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        if mismatched.is_empty() {
+            break (parents, requirements, versions);
+        }
+        ignored.extend(mismatched);
+    };
+
+    // Returns the chain of packages from the manifest to the package named `name`.
+    let get_chain = |name: &str| {
+        let mut chain = Vec::new();
+        let mut current = Some(name.to_owned());
+        while let Some(name) = current {
+            chain.push(format!("{}@{}", name, versions[&name].0));
+            current = parents[&name].clone();
+        }
+        chain.reverse();
+        chain
+    };
+
+    let mut resolved_packages = versions
+        .iter()
+        .map(|(name, (version, reason))| ResolvedPackage {
+            name: name.clone(),
+            version: version.clone(),
+            reason: *reason,
+            requirements: requirements
+                .get(name)
+                .into_iter()
+                .flatten()
+                .map(|(dependent, version)| Requirement {
+                    chain: get_chain(dependent),
+                    version: version.clone(),
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    resolved_packages.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    Ok(resolved_packages)
+}
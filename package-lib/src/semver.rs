@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::error;
 use std::fmt;
 use std::str::FromStr;
@@ -14,6 +15,7 @@ pub enum VersionError {
     UnexpectedCharAfter(Position, char),
     LeadingZero(Position),
     Overflow(Position),
+    InvalidPreRelease(String),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -43,6 +45,13 @@ impl fmt::Display for VersionError {
             }
             LeadingZero(pos) => write!(formatter, "Leading zero while parsing {}", pos),
             Overflow(pos) => write!(formatter, "Value of {} exceeds u16::MAX", pos),
+            InvalidPreRelease(pre_release) => {
+                write!(
+                    formatter,
+                    "Invalid pre-release identifiers '{}'",
+                    pre_release
+                )
+            }
         }
     }
 }
@@ -173,6 +182,113 @@ fn parse_dot(input: &str, pos: Position) -> Result<&str, VersionError> {
     }
 }
 
+/// Version of a package in a registry, which may have pre-release identifiers, such as
+/// `1.2.0-preview.3`. Build metadata is ignored. Versions are ordered by semantic version
+/// precedence, so pre-releases come before the release of the same version.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RegistryVersion {
+    pub version: Version,
+    /// Dot-separated pre-release identifiers without the leading `-`.
+    pub pre_release: Option<String>,
+}
+
+impl RegistryVersion {
+    pub fn is_pre_release(&self) -> bool {
+        self.pre_release.is_some()
+    }
+}
+
+impl From<Version> for RegistryVersion {
+    fn from(version: Version) -> Self {
+        RegistryVersion {
+            version,
+            pre_release: None,
+        }
+    }
+}
+
+impl fmt::Display for RegistryVersion {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match &self.pre_release {
+            Some(pre_release) => write!(fmt, "{}-{}", self.version, pre_release),
+            None => write!(fmt, "{}", self.version),
+        }
+    }
+}
+
+impl FromStr for RegistryVersion {
+    type Err = VersionError;
+
+    /// Parses a semantic version with optional pre-release identifiers and build metadata.
+    /// Numeric identifiers must not have leading zeros, so that versions that are equal in
+    /// precedence are also equal.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.split_once('+').map_or(value, |(value, _build)| value);
+        let Some((value, pre_release)) = value.split_once('-') else {
+            return Ok(Version::from_str(value)?.into());
+        };
+
+        let version = Version::from_str(value)?;
+        let is_valid = pre_release.split('.').all(|identifier| {
+            let is_numeric = identifier.bytes().all(|byte| byte.is_ascii_digit());
+            !identifier.is_empty()
+                && identifier
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+                && !(is_numeric && identifier.len() > 1 && identifier.starts_with('0'))
+        });
+        if !is_valid {
+            return Err(VersionError::InvalidPreRelease(pre_release.to_owned()));
+        }
+
+        Ok(RegistryVersion {
+            version,
+            pre_release: Some(pre_release.to_owned()),
+        })
+    }
+}
+
+/// Compares pre-release identifiers by semantic version precedence: numeric identifiers
+/// numerically and before alphanumeric ones, which are compared in ASCII order.
+fn compare_pre_release(a: &str, b: &str) -> Ordering {
+    let mut a_identifiers = a.split('.');
+    let mut b_identifiers = b.split('.');
+    loop {
+        let (a, b) = match (a_identifiers.next(), b_identifiers.next()) {
+            (Some(a), Some(b)) => (a, b),
+            (a, b) => return a.is_some().cmp(&b.is_some()),
+        };
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+impl Ord for RegistryVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.version.cmp(&other.version).then_with(|| {
+            match (&self.pre_release, &other.pre_release) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_pre_release(a, b),
+            }
+        })
+    }
+}
+
+impl PartialOrd for RegistryVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 struct VersionVisitor;
 
 impl Visitor<'_> for VersionVisitor {
@@ -0,0 +1,49 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use tar::Archive;
+
+use crate::{Error, Result};
+
+/// Path of the package manifest inside archives created by `npm pack`.
+pub static TARBALL_MANIFEST_PATH: &str = "package/package.json";
+
+/// Filename extensions of package tarballs.
+const TARBALL_EXTENSIONS: [&str; 2] = [".tgz", ".tar.gz"];
+
+/// Returns whether the file named `name` is a package tarball.
+pub fn is_tarball(name: &str) -> bool {
+    TARBALL_EXTENSIONS
+        .iter()
+        .any(|extension| name.ends_with(extension))
+}
+
+/// Reads the package manifest text from the tarball at `path` without extracting the archive.
+pub(crate) fn read_tarball_manifest_text(path: &Path) -> Result<String> {
+    let file = File::open(path).map_err(Error::io(path, "open"))?;
+    let mut archive = Archive::new(GzDecoder::new(file));
+
+    for entry in archive.entries().map_err(Error::io(path, "read archive"))? {
+        let mut entry = entry.map_err(Error::io(path, "read archive"))?;
+        let entry_path = entry.path().map_err(Error::io(path, "read archive"))?;
+        let entry_path = entry_path.strip_prefix(".").unwrap_or(&entry_path);
+        if entry_path != Path::new(TARBALL_MANIFEST_PATH) {
+            continue;
+        }
+
+        let mut text = String::new();
+        entry
+            .read_to_string(&mut text)
+            .map_err(Error::io(path, "read archive"))?;
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        return Ok(text.to_owned());
+    }
+
+    let error = io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("archive does not contain {}", TARBALL_MANIFEST_PATH),
+    );
+    Err(Error::io(path, "read archive")(error))
+}
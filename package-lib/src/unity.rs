@@ -0,0 +1,213 @@
+use std::error;
+use std::fmt;
+use std::path::Path;
+use std::result;
+use std::str::FromStr;
+
+use crate::{Error, Result, read_to_string};
+
+/// Path of the file that records the Unity version of a project, relative to the project.
+pub const PROJECT_VERSION_PATH: &str = "ProjectSettings/ProjectVersion.txt";
+
+/// Key of the Unity version in the project version file.
+const EDITOR_VERSION_KEY: &str = "m_EditorVersion:";
+
+/// Error parsing a Unity version.
+#[derive(Debug)]
+pub enum UnityVersionError {
+    Empty,
+    /// The major or minor version number is missing or not a number.
+    InvalidVersion(String),
+    /// The release, such as `21f1`, is not a patch number, release type and build number.
+    InvalidRelease(String),
+}
+
+impl error::Error for UnityVersionError {}
+
+impl fmt::Display for UnityVersionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        use self::UnityVersionError::*;
+        match self {
+            Empty => write!(formatter, "Empty Unity version string"),
+            InvalidVersion(version) => {
+                write!(formatter, "Invalid major and minor version '{}'", version)
+            }
+            InvalidRelease(release) => write!(formatter, "Invalid release '{}'", release),
+        }
+    }
+}
+
+/// Type of a Unity release, in release order.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub enum ReleaseType {
+    Alpha,
+    Beta,
+    Final,
+    Patch,
+}
+
+impl ReleaseType {
+    /// Returns the letter of the release type in Unity versions.
+    pub fn letter(self) -> char {
+        match self {
+            ReleaseType::Alpha => 'a',
+            ReleaseType::Beta => 'b',
+            ReleaseType::Final => 'f',
+            ReleaseType::Patch => 'p',
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'a' => Some(ReleaseType::Alpha),
+            'b' => Some(ReleaseType::Beta),
+            'f' => Some(ReleaseType::Final),
+            'p' => Some(ReleaseType::Patch),
+            _ => None,
+        }
+    }
+}
+
+/// Release of a Unity version, such as `21f1`, the `unityRelease` of packages.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub struct UnityRelease {
+    pub patch: u32,
+    pub release_type: ReleaseType,
+    pub build: u32,
+}
+
+impl fmt::Display for UnityRelease {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}{}{}",
+            self.patch,
+            self.release_type.letter(),
+            self.build
+        )
+    }
+}
+
+impl FromStr for UnityRelease {
+    type Err = UnityVersionError;
+
+    /// Parses a Unity release such as `21f1`. The `c1` suffix of China builds, such as `21f1c1`,
+    /// is ignored, as they are the same release as the build they are based on.
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let invalid = || UnityVersionError::InvalidRelease(value.to_owned());
+        let type_index = value
+            .find(|ch: char| !ch.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let (patch, rest) = value.split_at(type_index);
+        let mut chars = rest.chars();
+        let release_type = chars
+            .next()
+            .and_then(ReleaseType::from_letter)
+            .ok_or_else(invalid)?;
+        let is_number = |text: &str| text.bytes().all(|byte| byte.is_ascii_digit());
+        let build = match chars.as_str().split_once('c') {
+            Some((build, china_build)) if !china_build.is_empty() && is_number(china_build) => {
+                build
+            }
+            Some(_) => return Err(invalid()),
+            None => chars.as_str(),
+        };
+        if !is_number(build) {
+            return Err(invalid());
+        }
+
+        Ok(UnityRelease {
+            patch: patch.parse().map_err(|_| invalid())?,
+            release_type,
+            build: build.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Unity editor version such as `2022.3.21f1` or `6000.0.5b3`. Versions without a release, such
+/// as the `unity` field of packages, precede all releases of their major and minor version.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub struct UnityVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub release: Option<UnityRelease>,
+}
+
+impl UnityVersion {
+    /// Parses the minimum Unity version of a package from its `unity` field, such as `2022.3`,
+    /// and its optional `unityRelease` field, such as `21f1`.
+    pub fn from_package_fields(
+        unity: &str,
+        unity_release: Option<&str>,
+    ) -> result::Result<Self, UnityVersionError> {
+        let mut version = parse_major_minor(unity)?;
+        version.release = unity_release.map(UnityRelease::from_str).transpose()?;
+        Ok(version)
+    }
+
+    /// Returns the `unity` field of packages for the version, such as `2022.3`.
+    pub fn package_unity(&self) -> String {
+        format!("{}.{}", self.major, self.minor)
+    }
+}
+
+impl fmt::Display for UnityVersion {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}.{}", self.major, self.minor)?;
+        if let Some(release) = &self.release {
+            write!(formatter, ".{}", release)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for UnityVersion {
+    type Err = UnityVersionError;
+
+    /// Parses a Unity version such as `2022.3.21f1`, `2022.3.21f1c1` or `2022.3`.
+    fn from_str(value: &str) -> result::Result<Self, Self::Err> {
+        let (unity, release) = match value.match_indices('.').nth(1) {
+            Some((index, _)) => (&value[..index], Some(&value[index + 1..])),
+            None => (value, None),
+        };
+        Self::from_package_fields(unity, release)
+    }
+}
+
+/// Parses a `major.minor` Unity version without a release.
+fn parse_major_minor(value: &str) -> result::Result<UnityVersion, UnityVersionError> {
+    if value.is_empty() {
+        return Err(UnityVersionError::Empty);
+    }
+
+    let invalid = || UnityVersionError::InvalidVersion(value.to_owned());
+    let (major, minor) = value.split_once('.').ok_or_else(invalid)?;
+    let parse = |number: &str| {
+        if number.is_empty() || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        number.parse::<u32>().map_err(|_| invalid())
+    };
+
+    Ok(UnityVersion {
+        major: parse(major)?,
+        minor: parse(minor)?,
+        release: None,
+    })
+}
+
+/// Reads the Unity version of the project at `project_path` from its project version file.
+pub fn read_project_version(project_path: &Path) -> Result<UnityVersion> {
+    let path = project_path.join(PROJECT_VERSION_PATH);
+    let text = read_to_string(path.as_path())?;
+    let value = text
+        .lines()
+        .find_map(|line| line.strip_prefix(EDITOR_VERSION_KEY))
+        .unwrap_or_default()
+        .trim();
+    UnityVersion::from_str(value).map_err(|source| Error::UnityVersion {
+        path,
+        value: value.to_owned(),
+        source,
+    })
+}
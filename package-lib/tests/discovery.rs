@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::Path;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use package_lib::{
+    DiscoveryError, DiscoveryOptions, Error, PackageSource, find_packages,
+    find_packages_with_options, find_valid_packages, is_unity_ignored,
+};
+use tempfile::TempDir;
+
+mod common;
+
+use common::write_file;
+
+fn write_package(dir: &Path, name: &str) {
+    let manifest = format!(r#"{{"name": "{}", "version": "1.0.0"}}"#, name);
+    write_file(dir.join("package.json").as_path(), manifest.as_str());
+}
+
+/// Creates a repository tree with packages in places Unity, git and exclude patterns ignore.
+fn create_repository() -> TempDir {
+    let fixture = tempfile::tempdir().unwrap();
+    let root = fixture.path();
+    fs::create_dir(root.join(".git")).unwrap();
+    write_file(
+        root.join(".gitignore").as_path(),
+        "Packages/Nested/Ignored/\n",
+    );
+
+    let packages = root.join("Packages");
+    write_file(packages.join(".gitignore").as_path(), "Generated/\n");
+    write_package(packages.join("com.a").as_path(), "com.a");
+    write_package(
+        packages.join("com.a/Samples~/Demo").as_path(),
+        "com.a.sample",
+    );
+    write_package(packages.join("com.a/Tests~").as_path(), "com.a.tests");
+    write_package(packages.join("Tools/Samples~/com.s").as_path(), "com.s");
+    write_package(packages.join(".hidden/com.h").as_path(), "com.h");
+    write_package(packages.join("CVS/com.c").as_path(), "com.c");
+    write_package(packages.join("Backup.tmp/com.t").as_path(), "com.t");
+    write_package(packages.join("Generated/com.g").as_path(), "com.g");
+    write_package(packages.join("Legacy/com.l").as_path(), "com.l");
+    write_package(packages.join("Nested/com.n").as_path(), "com.n");
+    write_package(packages.join("Nested/Ignored/com.i").as_path(), "com.i");
+
+    fixture
+}
+
+fn get_names(packages: impl Iterator<Item = package_lib::Package>) -> Vec<String> {
+    let mut names = packages.map(|package| package.name).collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn unity_ignored_names() {
+    assert!(is_unity_ignored("Samples~"));
+    assert!(is_unity_ignored(".git"));
+    assert!(is_unity_ignored("cvs"));
+    assert!(is_unity_ignored("Old.tmp"));
+    assert!(!is_unity_ignored("Runtime"));
+    assert!(!is_unity_ignored("Editor~Tools"));
+}
+
+#[test]
+fn skips_unity_ignored_directories() {
+    let fixture = create_repository();
+    let names = get_names(find_packages(fixture.path().join("Packages")));
+    assert_eq!(names, ["com.a", "com.g", "com.i", "com.l", "com.n"]);
+}
+
+#[test]
+fn skips_gitignored_directories() {
+    let fixture = create_repository();
+    let options = DiscoveryOptions {
+        respect_gitignore: true,
+        ..Default::default()
+    };
+    let packages = find_packages_with_options(fixture.path().join("Packages"), &options).unwrap();
+    assert_eq!(get_names(packages), ["com.a", "com.l", "com.n"]);
+}
+
+#[test]
+fn skips_excluded_directories() {
+    let fixture = create_repository();
+    let options = DiscoveryOptions {
+        exclude: vec![String::from("Legacy"), String::from("**/Ignored")],
+        ..Default::default()
+    };
+    let packages = find_packages_with_options(fixture.path().join("Packages"), &options).unwrap();
+    assert_eq!(get_names(packages), ["com.a", "com.g", "com.n"]);
+}
+
+#[test]
+fn rejects_invalid_exclude_pattern() {
+    let fixture = create_repository();
+    let options = DiscoveryOptions {
+        exclude: vec![String::from("Legacy[")],
+        ..Default::default()
+    };
+    let result = find_packages_with_options(fixture.path().join("Packages"), &options);
+    assert!(matches!(result, Err(Error::Pattern { .. })));
+}
+
+fn write_tarball(path: &Path, manifest: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let encoder = GzEncoder::new(fs::File::create(path).unwrap(), Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, "package/package.json", manifest.as_bytes())
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+}
+
+#[test]
+fn lists_tarball_packages() {
+    let fixture = tempfile::tempdir().unwrap();
+    let tarball_path = fixture.path().join("Vendor/com.v-1.2.0.tgz");
+    write_tarball(
+        tarball_path.as_path(),
+        r#"{"name": "com.v", "version": "1.2.0"}"#,
+    );
+    write_file(fixture.path().join("Vendor/notes.txt").as_path(), "");
+
+    let packages = find_packages(fixture.path()).collect::<Vec<_>>();
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].name, "com.v");
+    assert_eq!(packages[0].version.to_string(), "1.2.0");
+    assert_eq!(packages[0].source, PackageSource::Tarball);
+    assert!(packages[0].path.ends_with("Vendor/com.v-1.2.0.tgz"));
+}
+
+#[test]
+fn reports_tarball_without_manifest() {
+    let fixture = tempfile::tempdir().unwrap();
+    let encoder = GzEncoder::new(
+        fs::File::create(fixture.path().join("empty.tgz")).unwrap(),
+        Compression::default(),
+    );
+    tar::Builder::new(encoder)
+        .into_inner()
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let results = find_packages(fixture.path()).checked().collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(results[0], Err(DiscoveryError::Manifest(_))));
+}
+
+#[test]
+fn fails_strict_discovery_of_invalid_packages() {
+    let fixture = tempfile::tempdir().unwrap();
+    write_package(fixture.path().join("com.a").as_path(), "com.a");
+    write_file(fixture.path().join("com.b/package.json").as_path(), "{");
+
+    let mut warnings = Vec::new();
+    let packages = find_valid_packages(
+        fixture.path(),
+        &DiscoveryOptions::default(),
+        false,
+        |error| warnings.push(error.to_string()),
+    )
+    .unwrap();
+    assert_eq!(get_names(packages.into_iter()), ["com.a"]);
+    assert_eq!(warnings.len(), 1);
+
+    let result = find_valid_packages(fixture.path(), &DiscoveryOptions::default(), true, |_| {});
+    assert!(matches!(result, Err(Error::InvalidPackages { count: 1 })));
+}
+
+#[test]
+fn traverses_submodules_when_included() {
+    let fixture = tempfile::tempdir().unwrap();
+    write_package(fixture.path().join("com.a").as_path(), "com.a");
+    write_file(
+        fixture.path().join("Vendor/.git").as_path(),
+        "gitdir: ../.git/modules/Vendor\n",
+    );
+    write_package(fixture.path().join("Vendor/com.s").as_path(), "com.s");
+
+    let names = get_names(find_packages(fixture.path()));
+    assert_eq!(names, ["com.a"]);
+
+    let options = DiscoveryOptions {
+        include_submodules: true,
+        ..Default::default()
+    };
+    let mut packages = find_packages_with_options(fixture.path(), &options)
+        .unwrap()
+        .collect::<Vec<_>>();
+    packages.sort_unstable();
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0].source, PackageSource::Directory);
+    assert_eq!(packages[1].name, "com.s");
+    assert_eq!(packages[1].source, PackageSource::Submodule);
+}
@@ -0,0 +1,136 @@
+use package_lib::{LintIssue, Package, find_packages, lint_project, read_project_version};
+use tempfile::TempDir;
+
+mod common;
+
+use common::{MANIFEST_PATH, create_fixture, read_manifest, write_file};
+
+/// Creates a project whose local packages require a higher dependency version and Unity version
+/// than the project has, and depend on missing packages.
+fn create_project() -> TempDir {
+    create_fixture(&[
+        (
+            "ProjectSettings/ProjectVersion.txt",
+            "m_EditorVersion: 2021.3.5f1\nm_EditorVersionWithRevision: 2021.3.5f1 (40eb3a945986)\n",
+        ),
+        (
+            MANIFEST_PATH,
+            r#"{
+                "dependencies": {"com.a.b": "file:../Local/b", "com.a.c": "1.0.0"},
+                "scopedRegistries": [{"name": "A", "url": "https://a.test", "scopes": ["com.reg"]}]
+            }"#,
+        ),
+        (
+            "Local/b/package.json",
+            r#"{"name": "com.a.b", "version": "1.2.0", "unity": "2021.3", "unityRelease": "9f1",
+                "dependencies": {"com.a.c": "1.5.0", "com.reg.d": "1.0.0", "com.unity.ugui": "1.0.0",
+                                 "com.other.e": "2.0.0"}}"#,
+        ),
+        (
+            "Local/c/package.json",
+            r#"{"name": "com.a.c", "version": "1.0.0", "unity": "2021.3", "unityRelease": "2f1"}"#,
+        ),
+    ])
+}
+
+fn lint(fixture: &TempDir) -> Vec<LintIssue> {
+    let root = fixture.path();
+    let manifest = read_manifest(root);
+    let packages = find_packages(root).collect::<Vec<Package>>();
+    let project_unity_version = read_project_version(root).unwrap();
+    lint_project(
+        &manifest,
+        root.join("Packages").as_path(),
+        packages.as_slice(),
+        Some(&project_unity_version),
+    )
+    .unwrap()
+}
+
+#[test]
+fn reports_project_problems() {
+    let fixture = create_project();
+    assert_eq!(
+        lint(&fixture),
+        [
+            LintIssue::DependencyBelowRequirement {
+                package: "com.a.b".to_owned(),
+                dependency: "com.a.c".to_owned(),
+                required: "1.5.0".to_owned(),
+                pinned: "1.0.0".to_owned(),
+            },
+            LintIssue::MissingDependency {
+                package: Some("com.a.b".to_owned()),
+                dependency: "com.other.e".to_owned(),
+                version: "2.0.0".to_owned(),
+            },
+            LintIssue::UnityVersionTooLow {
+                package: "com.a.b".to_owned(),
+                required: "2021.3.9f1".to_owned(),
+                project: "2021.3.5f1".to_owned(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn reports_missing_local_packages() {
+    let fixture = create_project();
+    write_file(
+        fixture.path().join(MANIFEST_PATH).as_path(),
+        r#"{"dependencies": {"com.a.gone": "file:../Gone"}}"#,
+    );
+    let issues = lint(&fixture);
+    assert!(issues.contains(&LintIssue::MissingDependency {
+        package: None,
+        dependency: "com.a.gone".to_owned(),
+        version: "file:../Gone".to_owned(),
+    }));
+}
+
+#[test]
+fn counts_only_loaded_local_packages_as_present() {
+    let fixture = create_project();
+    let root = fixture.path();
+    write_file(
+        root.join("Local/b/package.json").as_path(),
+        r#"{"name": "com.a.b", "version": "1.2.0",
+            "dependencies": {"com.a.embedded": "1.0.0", "com.a.unused": "1.0.0"}}"#,
+    );
+    write_file(
+        root.join("Packages/com.a.embedded/package.json").as_path(),
+        r#"{"name": "com.a.embedded", "version": "1.0.0"}"#,
+    );
+    write_file(
+        root.join("Local/unused/package.json").as_path(),
+        r#"{"name": "com.a.unused", "version": "1.0.0"}"#,
+    );
+    let issues = lint(&fixture);
+    assert!(issues.contains(&LintIssue::MissingDependency {
+        package: Some("com.a.b".to_owned()),
+        dependency: "com.a.unused".to_owned(),
+        version: "1.0.0".to_owned(),
+    }));
+    assert!(!issues.iter().any(|issue| matches!(
+        issue,
+        LintIssue::MissingDependency { dependency, .. } if dependency == "com.a.embedded"
+    )));
+}
+
+#[test]
+fn serializes_issues_with_kind() {
+    let issue = LintIssue::UnityVersionTooLow {
+        package: "com.a.b".to_owned(),
+        required: "2022.1".to_owned(),
+        project: "2021.3.5f1".to_owned(),
+    };
+    assert_eq!(
+        serde_json::to_value(&issue).unwrap(),
+        serde_json::json!({
+            "kind": "unityVersionTooLow",
+            "package": "com.a.b",
+            "required": "2022.1",
+            "project": "2021.3.5f1",
+        })
+    );
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+use flate2::read::GzDecoder;
+use package_lib::{Error, get_pack_contents, pack_package, write_package_tarball};
+use tempfile::TempDir;
+
+mod common;
+
+use common::{create_fixture, write_file};
+
+/// Creates a package directory with files npm includes, ignores and always includes.
+fn create_package(manifest: &str) -> TempDir {
+    let mut files = vec![
+        ("package.json", manifest),
+        ("Runtime/.npmignore", "Generated/\n"),
+    ];
+    for path in [
+        "README.md",
+        "LICENSE",
+        "CHANGELOG.md",
+        "Runtime/Tool.cs",
+        "Runtime/Tool.cs.meta",
+        "Editor/Inspector.cs",
+        "Samples~/Demo/Demo.cs",
+        "Documentation~/index.md",
+        "node_modules/dep/index.js",
+        ".git/HEAD",
+        ".DS_Store",
+        "Runtime/Generated/Cache.bin",
+    ] {
+        files.push((path, path));
+    }
+    create_fixture(&files)
+}
+
+fn get_paths(files: &[PathBuf]) -> Vec<String> {
+    files
+        .iter()
+        .map(|path| path.to_str().unwrap().replace('\\', "/"))
+        .collect()
+}
+
+#[test]
+fn packs_all_files_except_ignored() {
+    let fixture = create_package(r#"{"name": "com.a", "version": "1.0.0"}"#);
+    write_file(fixture.path().join(".npmignore").as_path(), "Editor/\n");
+
+    let contents = get_pack_contents(fixture.path()).unwrap();
+    assert_eq!(contents.tarball_filename(), "com.a-1.0.0.tgz");
+    assert_eq!(
+        get_paths(contents.files.as_slice()),
+        [
+            "CHANGELOG.md",
+            "Documentation~/index.md",
+            "LICENSE",
+            "README.md",
+            "Runtime/Tool.cs",
+            "Runtime/Tool.cs.meta",
+            "Samples~/Demo/Demo.cs",
+            "package.json",
+        ]
+    );
+}
+
+#[test]
+fn packs_only_files_field_and_always_included_files() {
+    let fixture = create_package(
+        r#"{"name": "@scope/a", "version": "2.0.0", "files": ["Runtime", "Samples~/**/*.cs"]}"#,
+    );
+    // The ignore file in the package root does not apply with a files field.
+    write_file(fixture.path().join(".npmignore").as_path(), "Runtime/\n");
+
+    let contents = get_pack_contents(fixture.path()).unwrap();
+    assert_eq!(contents.tarball_filename(), "scope-a-2.0.0.tgz");
+    assert_eq!(
+        get_paths(contents.files.as_slice()),
+        [
+            "LICENSE",
+            "README.md",
+            "Runtime/Tool.cs",
+            "Runtime/Tool.cs.meta",
+            "Samples~/Demo/Demo.cs",
+            "package.json",
+        ]
+    );
+}
+
+#[test]
+fn rejects_invalid_files_pattern() {
+    let fixture = create_package(r#"{"name": "com.a", "version": "1.0.0", "files": ["Run["]}"#);
+    let result = get_pack_contents(fixture.path());
+    assert!(matches!(result, Err(Error::Pattern { .. })));
+}
+
+#[test]
+fn writes_reproducible_tarball() {
+    let fixture = create_package(r#"{"name": "com.a", "version": "1.0.0"}"#);
+    let contents = get_pack_contents(fixture.path()).unwrap();
+    let first = write_package_tarball(fixture.path(), &contents, Vec::new()).unwrap();
+
+    // Touching a file must not change the tarball.
+    write_file(
+        fixture.path().join("Runtime/Tool.cs").as_path(),
+        "Runtime/Tool.cs",
+    );
+    let output = tempfile::tempdir().unwrap();
+    let (_, tarball_path) = pack_package(fixture.path(), output.path()).unwrap();
+    let second = fs::read(tarball_path.as_path()).unwrap();
+    assert_eq!(first, second);
+
+    let mut archive = tar::Archive::new(GzDecoder::new(second.as_slice()));
+    let mut paths = Vec::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        assert_eq!(entry.header().mtime().unwrap(), 499_162_500);
+        let path = entry.path().unwrap().to_str().unwrap().to_owned();
+        if path == "package/package.json" {
+            let mut text = String::new();
+            entry.read_to_string(&mut text).unwrap();
+            assert!(text.contains("com.a"));
+        }
+        paths.push(path);
+    }
+    assert_eq!(paths.len(), contents.files.len());
+    assert!(paths.iter().all(|path| path.starts_with("package/")));
+    assert!(paths.is_sorted());
+}
@@ -0,0 +1,198 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use package_lib::{
+    Error, Manifest, Registry, find_registry_token, get_pack_contents, write_package_tarball,
+};
+use serde_json::Value;
+use tiny_http::{Response, Server};
+
+mod common;
+
+use common::write_file;
+
+/// Request received by the mock registry.
+#[derive(Debug)]
+struct Request {
+    method: String,
+    url: String,
+    authorization: Option<String>,
+    body: String,
+}
+
+/// Starts a registry on a free local port that answers `responses` in order with status and body
+/// and sends the requests it receives to the returned receiver.
+fn start_registry(responses: Vec<(u16, &'static str)>) -> (String, Receiver<Request>) {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/npm/", server.server_addr().to_ip().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for (status, body) in responses {
+            let mut request = server.recv().unwrap();
+            let mut request_body = String::new();
+            request
+                .as_reader()
+                .read_to_string(&mut request_body)
+                .unwrap();
+            let authorization = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Authorization"))
+                .map(|header| header.value.to_string());
+            sender
+                .send(Request {
+                    method: request.method().to_string(),
+                    url: request.url().to_owned(),
+                    authorization,
+                    body: request_body,
+                })
+                .unwrap();
+            request
+                .respond(Response::from_string(body).with_status_code(status))
+                .unwrap();
+        }
+    });
+    (url, receiver)
+}
+
+#[test]
+fn unknown_package_has_no_versions() {
+    let (url, requests) = start_registry(vec![(404, r#"{"error": "not found"}"#)]);
+    let registry = Registry::new(url.as_str(), None);
+
+    assert!(registry.get_versions("com.a").unwrap().is_empty());
+    let request = requests.recv().unwrap();
+    assert_eq!(request.method, "GET");
+    assert_eq!(request.url, "/npm/com.a");
+    assert_eq!(request.authorization, None);
+}
+
+#[test]
+fn lists_published_versions() {
+    let (url, _requests) = start_registry(vec![(
+        200,
+        r#"{"name": "com.a", "versions": {"1.0.0": {}, "1.1.0": {}}}"#,
+    )]);
+    let registry = Registry::new(url.as_str(), None);
+    assert_eq!(registry.get_versions("com.a").unwrap(), ["1.0.0", "1.1.0"]);
+}
+
+#[test]
+fn reports_rejected_token() {
+    let (url, _requests) = start_registry(vec![(401, "")]);
+    let registry = Registry::new(url.as_str(), Some(String::from("expired")));
+
+    let error = registry.get_versions("com.a").unwrap_err();
+    assert!(matches!(error, Error::Registry { .. }));
+    assert!(error.hint().unwrap().contains("token"));
+}
+
+#[test]
+fn publishes_tarball_with_checksums() {
+    let fixture = tempfile::tempdir().unwrap();
+    write_file(
+        fixture.path().join("package.json").as_path(),
+        r#"{"name": "com.a", "version": "1.2.0", "unity": "2022.3"}"#,
+    );
+    write_file(fixture.path().join("Runtime/A.cs").as_path(), "class A {}");
+    let contents = get_pack_contents(fixture.path()).unwrap();
+    let tarball = write_package_tarball(fixture.path(), &contents, Vec::new()).unwrap();
+
+    let (url, requests) = start_registry(vec![(201, r#"{"ok": true}"#)]);
+    let registry = Registry::new(url.as_str(), Some(String::from("secret")));
+    registry
+        .publish(fixture.path(), &contents, tarball.as_slice())
+        .unwrap();
+
+    let request = requests.recv().unwrap();
+    assert_eq!(request.method, "PUT");
+    assert_eq!(request.url, "/npm/com.a");
+    assert_eq!(request.authorization.as_deref(), Some("Bearer secret"));
+
+    let document: Value = serde_json::from_str(request.body.as_str()).unwrap();
+    assert_eq!(document["dist-tags"]["latest"], "1.2.0");
+    let version = &document["versions"]["1.2.0"];
+    assert_eq!(version["unity"], "2022.3");
+    assert_eq!(version["_id"], "com.a@1.2.0");
+    assert_eq!(version["dist"]["shasum"].as_str().unwrap().len(), 40);
+    assert!(
+        version["dist"]["integrity"]
+            .as_str()
+            .unwrap()
+            .starts_with("sha512-")
+    );
+    assert!(
+        version["dist"]["tarball"]
+            .as_str()
+            .unwrap()
+            .ends_with("/npm/com.a/-/com.a-1.2.0.tgz")
+    );
+    let data = document["_attachments"]["com.a-1.2.0.tgz"]["data"]
+        .as_str()
+        .unwrap();
+    assert_eq!(BASE64.decode(data).unwrap(), tarball);
+}
+
+#[test]
+fn reads_token_of_registry_from_npmrc() {
+    let fixture = tempfile::tempdir().unwrap();
+    let npmrc_path = fixture.path().join(".npmrc");
+    write_file(
+        npmrc_path.as_path(),
+        "registry=https://registry.npmjs.org/\n\
+         //registry.example.com/:_authToken=root\n\
+         //registry.example.com/npm/:_authToken=\"scoped\"\n",
+    );
+    let npmrc_paths = [fixture.path().join("missing/.npmrc"), npmrc_path];
+
+    let token = find_registry_token("https://registry.example.com/npm", &npmrc_paths);
+    assert_eq!(token.as_deref(), Some("scoped"));
+    let token = find_registry_token("https://registry.example.com/other/", &npmrc_paths);
+    assert_eq!(token.as_deref(), Some("root"));
+    assert_eq!(
+        find_registry_token("https://other.example.com/", &npmrc_paths),
+        None
+    );
+}
+
+#[test]
+fn sends_npmrc_token_of_registry_with_port() {
+    let (url, requests) = start_registry(vec![(404, "")]);
+    let fixture = tempfile::tempdir().unwrap();
+    let npmrc_path = fixture.path().join(".npmrc");
+    // Keys of registries with a port, such as //127.0.0.1:4873/npm/:_authToken.
+    let registry_key = url.strip_prefix("http:").unwrap();
+    write_file(
+        npmrc_path.as_path(),
+        format!("{}:_authToken=local\n", registry_key).as_str(),
+    );
+
+    let token = find_registry_token(url.as_str(), &[npmrc_path]);
+    assert_eq!(token.as_deref(), Some("local"));
+    let registry = Registry::new(url.as_str(), token);
+    assert!(registry.get_versions("com.a").unwrap().is_empty());
+    let request = requests.recv().unwrap();
+    assert_eq!(request.authorization.as_deref(), Some("Bearer local"));
+}
+
+#[test]
+fn picks_registry_with_longest_matching_scope() {
+    let manifest: Manifest = serde_json::from_str(
+        r#"{
+            "dependencies": {},
+            "scopedRegistries": [
+                {"name": "Company", "url": "https://a.example.com", "scopes": ["com.company"]},
+                {"name": "Tools", "url": "https://b.example.com", "scopes": ["com.company.tools"]}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let registry = |name| manifest.get_scoped_registry(name).map(|r| r.name.as_str());
+    assert_eq!(registry("com.company.core"), Some("Company"));
+    assert_eq!(registry("com.company.tools.editor"), Some("Tools"));
+    assert_eq!(registry("com.companyx"), None);
+}
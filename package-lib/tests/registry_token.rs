@@ -0,0 +1,31 @@
+use std::env;
+
+use package_lib::find_registry_token;
+
+mod common;
+
+use common::write_file;
+
+// This is the only test of its binary, so setting the environment variable races no other test.
+#[test]
+fn sends_npm_token_only_to_registries_configured_with_it() {
+    // SAFETY: no other thread of the test binary reads the environment.
+    unsafe { env::set_var("NPM_TOKEN", "secret") };
+    let fixture = tempfile::tempdir().unwrap();
+    let npmrc_path = fixture.path().join(".npmrc");
+    write_file(
+        npmrc_path.as_path(),
+        "//registry.example.com/:_authToken=${NPM_TOKEN}\n",
+    );
+    let npmrc_paths = [npmrc_path];
+
+    assert_eq!(
+        find_registry_token("https://registry.example.com/", &npmrc_paths).as_deref(),
+        Some("secret")
+    );
+    assert_eq!(
+        find_registry_token("https://other.example.com/", &npmrc_paths),
+        None
+    );
+    assert_eq!(find_registry_token("https://other.example.com/", &[]), None);
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use package_lib::{
+    Package, RegistryVersion, ResolutionReason, ResolutionStrategy, ResolvedPackage, find_packages,
+    resolve_dependencies,
+};
+use tempfile::TempDir;
+
+mod common;
+
+use common::{MANIFEST_PATH, create_fixture, read_manifest, write_file};
+
+/// Creates a project with an embedded package, local packages outside the packages directory and
+/// a manifest that references one of them with a `file:` dependency.
+fn create_project() -> TempDir {
+    create_fixture(&[
+        (
+            MANIFEST_PATH,
+            r#"{"dependencies": {"com.a.b": "file:../Local/b", "com.a.c": "1.0.0"}}"#,
+        ),
+        (
+            "Packages/com.a.embedded/package.json",
+            r#"{"name": "com.a.embedded", "version": "0.1.0", "dependencies": {"com.a.d": "1.0.0"}}"#,
+        ),
+        (
+            "Local/b/package.json",
+            r#"{"name": "com.a.b", "version": "1.2.0",
+                "dependencies": {"com.a.c": "1.5.0", "com.a.d": "1.1.0"}}"#,
+        ),
+        (
+            "Local/c/package.json",
+            r#"{"name": "com.a.c", "version": "1.5.0", "dependencies": {"com.a.e": "3.0.0"}}"#,
+        ),
+    ])
+}
+
+fn resolve(
+    fixture: &TempDir,
+    available_versions: &[(&str, &[&str])],
+    strategy: ResolutionStrategy,
+) -> Vec<ResolvedPackage> {
+    let root = fixture.path();
+    let manifest = read_manifest(root);
+    let packages = find_packages(root).collect::<Vec<Package>>();
+    let available_versions = available_versions
+        .iter()
+        .map(|(name, versions)| {
+            let versions = versions.iter().map(|version| version.parse().unwrap());
+            (name.to_string(), versions.collect::<Vec<RegistryVersion>>())
+        })
+        .collect::<HashMap<_, _>>();
+    resolve_dependencies(
+        &manifest,
+        root.join("Packages").as_path(),
+        packages.as_slice(),
+        &available_versions,
+        strategy,
+    )
+    .unwrap()
+}
+
+fn get_versions(resolved_packages: &[ResolvedPackage]) -> Vec<(&str, &str, ResolutionReason)> {
+    resolved_packages
+        .iter()
+        .map(|package| {
+            (
+                package.name.as_str(),
+                package.version.as_str(),
+                package.reason,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn direct_dependencies_win_over_requirements() {
+    let fixture = create_project();
+    let strategy = ResolutionStrategy::Lowest;
+    let resolved_packages = resolve(&fixture, &[], strategy);
+    assert_eq!(
+        get_versions(resolved_packages.as_slice()),
+        [
+            ("com.a.b", "1.2.0", ResolutionReason::Local),
+            ("com.a.c", "1.0.0", ResolutionReason::Direct),
+            ("com.a.d", "1.1.0", ResolutionReason::Requirements(strategy)),
+            ("com.a.embedded", "0.1.0", ResolutionReason::Embedded),
+        ]
+    );
+
+    let requirements = &resolved_packages[2].requirements;
+    assert_eq!(requirements.len(), 2);
+    assert!(requirements.iter().any(
+        |requirement| requirement.chain == ["com.a.b@1.2.0"] && requirement.version == "1.1.0"
+    ));
+}
+
+#[test]
+fn strategies_choose_available_versions() {
+    let fixture = create_project();
+    let available_versions: &[(&str, &[&str])] = &[(
+        "com.a.d",
+        &["1.0.0", "1.1.0", "1.1.3", "1.2.0", "1.3.0-pre.1", "2.0.0"],
+    )];
+    for (strategy, version) in [
+        (ResolutionStrategy::Lowest, "1.1.0"),
+        (ResolutionStrategy::HighestPatch, "1.1.3"),
+        (ResolutionStrategy::HighestMinor, "1.2.0"),
+        (ResolutionStrategy::Highest, "2.0.0"),
+    ] {
+        let resolved_packages = resolve(&fixture, available_versions, strategy);
+        let package = resolved_packages
+            .iter()
+            .find(|package| package.name == "com.a.d")
+            .unwrap();
+        assert_eq!(package.version, version, "{}", strategy);
+    }
+}
+
+#[test]
+fn follows_indirect_local_packages_of_resolved_version_only() {
+    let fixture = create_project();
+    write_file(
+        fixture.path().join(MANIFEST_PATH).as_path(),
+        r#"{"dependencies": {"com.a.b": "file:../Local/b"}}"#,
+    );
+    let available_versions: &[(&str, &[&str])] = &[("com.a.c", &["1.5.0", "2.0.0"])];
+    let get_names = |strategy| {
+        resolve(&fixture, available_versions, strategy)
+            .into_iter()
+            .map(|package| format!("{}@{}", package.name, package.version))
+            .collect::<Vec<_>>()
+    };
+
+    // The local package of com.a.c is 1.5.0 and requires com.a.e, which 2.0.0 may not.
+    assert!(get_names(ResolutionStrategy::Lowest).contains(&"com.a.e@3.0.0".to_owned()));
+    let names = get_names(ResolutionStrategy::Highest);
+    assert!(names.contains(&"com.a.c@2.0.0".to_owned()));
+    assert!(!names.iter().any(|name| name.starts_with("com.a.e@")));
+}
+
+#[test]
+fn parses_manifest_strategy_names() {
+    for name in ["lowest", "highestPatch", "highestMinor", "highest"] {
+        let strategy: ResolutionStrategy = name.parse().unwrap();
+        assert_eq!(strategy.name(), name);
+    }
+    assert!("newest".parse::<ResolutionStrategy>().is_err());
+}
@@ -0,0 +1,49 @@
+use std::str::FromStr;
+
+use package_lib::{RegistryVersion, Version, VersionError};
+
+fn parse(value: &str) -> RegistryVersion {
+    RegistryVersion::from_str(value).unwrap()
+}
+
+#[test]
+fn parses_pre_release_and_ignores_build_metadata() {
+    let version = parse("1.2.3-preview.4+build.5");
+    assert_eq!(version.version, Version::from_str("1.2.3").unwrap());
+    assert_eq!(version.pre_release.as_deref(), Some("preview.4"));
+    assert_eq!(version.to_string(), "1.2.3-preview.4");
+    assert!(!parse("1.2.3").is_pre_release());
+
+    for value in ["1.2.3-pre..1", "1.0.0-01", "1.0.0-rc.00"] {
+        assert!(
+            matches!(
+                RegistryVersion::from_str(value),
+                Err(VersionError::InvalidPreRelease(_))
+            ),
+            "{}",
+            value
+        );
+    }
+    // Leading zeros are allowed in alphanumeric identifiers.
+    assert_eq!(parse("1.0.0-0a.0").pre_release.as_deref(), Some("0a.0"));
+    assert!(RegistryVersion::from_str("1.2").is_err());
+}
+
+#[test]
+fn orders_by_semantic_version_precedence() {
+    let ordered = [
+        "1.0.0-alpha",
+        "1.0.0-alpha.1",
+        "1.0.0-alpha.beta",
+        "1.0.0-beta",
+        "1.0.0-beta.2",
+        "1.0.0-beta.11",
+        "1.0.0-rc.1",
+        "1.0.0",
+        "1.0.1",
+        "1.1.0-exp.0",
+    ];
+    for pair in ordered.windows(2) {
+        assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+    }
+}
@@ -0,0 +1,102 @@
+use package_lib::{
+    Error, PROJECT_VERSION_PATH, ReleaseType, UnityRelease, UnityVersion, read_project_version,
+};
+
+mod common;
+
+use common::{create_fixture, write_file};
+
+fn parse(value: &str) -> UnityVersion {
+    value.parse().unwrap()
+}
+
+#[test]
+fn parses_editor_versions() {
+    assert_eq!(
+        parse("6000.0.5b3"),
+        UnityVersion {
+            major: 6000,
+            minor: 0,
+            release: Some(UnityRelease {
+                patch: 5,
+                release_type: ReleaseType::Beta,
+                build: 3,
+            }),
+        }
+    );
+    for value in ["2022.3.21f1", "2021.3.0a12", "2019.4.40p2", "2022.3"] {
+        assert_eq!(parse(value).to_string(), value);
+    }
+    for value in [
+        "",
+        "2022",
+        "2022.x",
+        "2022.3.21",
+        "2022.3.21x1",
+        "2022.3.f1",
+        "2022.3.21f",
+        "2022.3.21f1c",
+        "2022.3.21f1cx",
+    ] {
+        assert!(value.parse::<UnityVersion>().is_err(), "{}", value);
+    }
+}
+
+#[test]
+fn ignores_china_builds() {
+    assert_eq!(parse("2022.3.21f1c1"), parse("2022.3.21f1"));
+    assert_eq!(parse("2022.3.21f1c1").to_string(), "2022.3.21f1");
+    assert_eq!(
+        UnityVersion::from_package_fields("2021.3", Some("9f1c2")).unwrap(),
+        parse("2021.3.9f1")
+    );
+}
+
+#[test]
+fn orders_release_types() {
+    let versions = [
+        "2021.3",
+        "2021.3.0a12",
+        "2021.3.0b1",
+        "2021.3.0f1",
+        "2021.3.0p1",
+        "2021.3.1a1",
+        "2021.3.10f1",
+        "2022.1.0a1",
+        "6000.0.5b3",
+    ];
+    for pair in versions.windows(2) {
+        assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+    }
+}
+
+#[test]
+fn converts_package_fields() {
+    let version = UnityVersion::from_package_fields("2022.3", Some("21f1")).unwrap();
+    assert_eq!(version, parse("2022.3.21f1"));
+    assert_eq!(version.package_unity(), "2022.3");
+    assert_eq!(
+        UnityVersion::from_package_fields("2022.3", None).unwrap(),
+        parse("2022.3")
+    );
+    assert!(UnityVersion::from_package_fields("2022.3", Some("21")).is_err());
+}
+
+#[test]
+fn reads_project_version() {
+    let fixture = create_fixture(&[(
+        PROJECT_VERSION_PATH,
+        "m_EditorVersion: 2022.3.21f1\r\nm_EditorVersionWithRevision: 2022.3.21f1 (bf07a6a6d7e6)\r\n",
+    )]);
+    let version_path = fixture.path().join(PROJECT_VERSION_PATH);
+    assert_eq!(
+        read_project_version(fixture.path()).unwrap(),
+        parse("2022.3.21f1")
+    );
+
+    write_file(version_path.as_path(), "m_EditorVersionWithRevision: x\n");
+    assert!(matches!(
+        read_project_version(fixture.path()),
+        Err(Error::UnityVersion { .. })
+    ));
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use package_lib::read_json;
+use serde::Deserialize;
+
+use crate::Result;
+
+/// Path of the config file read if no config file is given, relative to the working directory.
+const DEFAULT_CONFIG_PATH: &str = "package-lint.json";
+
+/// Default name of the changelog file of packages.
+const DEFAULT_CHANGELOG_FILENAME: &str = "CHANGELOG.md";
+
+/// Severity of the problems found by a rule, in increasing order.
+#[derive(Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Severity {
+    /// The rule is not checked.
+    Off,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            Severity::Off => "off",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Convention checked for each package.
+#[derive(Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Rule {
+    /// The package has a valid `package.json`.
+    PackageJson,
+    /// The package has a changelog.
+    Changelog,
+    /// The package has a `LICENSE.md`.
+    License,
+    /// The package has a readme.
+    Readme,
+    /// The package name is lowercase reverse-DNS and under 214 characters.
+    Name,
+    /// The package has a `displayName`.
+    DisplayName,
+    /// Scripts are in the `Runtime`, `Editor`, `Tests/Runtime` or `Tests/Editor` folders.
+    FolderLayout,
+    /// Each folder with scripts has an assembly definition.
+    AssemblyDefinition,
+}
+
+impl Rule {
+    /// Returns the name of the rule in config files and output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::PackageJson => "package-json",
+            Rule::Changelog => "changelog",
+            Rule::License => "license",
+            Rule::Readme => "readme",
+            Rule::Name => "name",
+            Rule::DisplayName => "display-name",
+            Rule::FolderLayout => "folder-layout",
+            Rule::AssemblyDefinition => "assembly-definition",
+        }
+    }
+
+    /// Returns the severity of the rule if the config does not set it.
+    fn default_severity(self) -> Severity {
+        match self {
+            Rule::Readme | Rule::DisplayName => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// Settings of a repository, read from a JSON file such as
+/// `{"rules": {"readme": "error", "license": "off"}, "changelogFilename": "release_notes.md"}`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Severities of the rules that differ from their defaults.
+    #[serde(default)]
+    pub rules: BTreeMap<Rule, Severity>,
+    /// Name of the changelog file of packages.
+    #[serde(default = "default_changelog_filename")]
+    pub changelog_filename: String,
+}
+
+fn default_changelog_filename() -> String {
+    DEFAULT_CHANGELOG_FILENAME.to_owned()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            rules: BTreeMap::new(),
+            changelog_filename: default_changelog_filename(),
+        }
+    }
+}
+
+impl Config {
+    /// Returns the severity of the problems found by `rule`.
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.rules
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+/// Reads the config file at `config_path`, or the default config file if no path is given and
+/// the file exists. Returns the default config otherwise.
+pub(crate) fn read_config(config_path: Option<&str>) -> Result<Config> {
+    match config_path {
+        Some(config_path) => Ok(read_json(config_path)?),
+        None if Path::new(DEFAULT_CONFIG_PATH).is_file() => Ok(read_json(DEFAULT_CONFIG_PATH)?),
+        None => Ok(Config::default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Rule, Severity};
+
+    #[test]
+    fn overrides_default_severities() {
+        let config: Config =
+            serde_json::from_str(r#"{"rules": {"readme": "error", "license": "off"}}"#).unwrap();
+        assert_eq!(config.severity(Rule::Readme), Severity::Error);
+        assert_eq!(config.severity(Rule::License), Severity::Off);
+        assert_eq!(config.severity(Rule::DisplayName), Severity::Warning);
+        assert_eq!(config.severity(Rule::Changelog), Severity::Error);
+        assert_eq!(config.changelog_filename, "CHANGELOG.md");
+    }
+
+    #[test]
+    fn rejects_unknown_rules_and_fields() {
+        for text in [
+            r#"{"rules": {"unknown": "error"}}"#,
+            r#"{"rules": {"readme": "fatal"}}"#,
+            r#"{"rule": {}}"#,
+        ] {
+            assert!(serde_json::from_str::<Config>(text).is_err(), "{}", text);
+        }
+    }
+}
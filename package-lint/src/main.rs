@@ -0,0 +1,160 @@
+use std::env;
+use std::error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::result;
+
+use clap::{self, Parser};
+use package_lib::{
+    DiscoveryError, PACKAGE_MANIFEST_FILENAME, PackageSource, find_packages,
+    print_discovery_warning, print_error,
+};
+
+mod config;
+mod rules;
+
+#[cfg(test)]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use config::{Rule, Severity, read_config};
+use rules::{Linter, Problem};
+
+pub(crate) type Result<T> = result::Result<T, Box<dyn error::Error>>;
+
+/// Exit code if problems of error severity were found.
+const PROBLEMS_EXIT_CODE: u8 = 1;
+
+/// Exit code if the packages could not be checked, such as for an invalid config file.
+const FAILURE_EXIT_CODE: u8 = 2;
+
+#[derive(Parser, Debug)]
+#[clap(version, about, long_about = None)]
+pub(crate) struct Options {
+    /// Paths to package directories or directories to search for packages
+    #[clap(default_value = ".")]
+    pub paths: Vec<String>,
+    /// JSON config file with the severities of the rules; defaults to package-lint.json in the
+    /// working directory, if it exists
+    #[clap(short, long, value_name = "PATH")]
+    pub config: Option<String>,
+    /// Also fail if only problems of warning severity were found
+    #[clap(long)]
+    pub deny_warnings: bool,
+    /// Verbose output
+    #[clap(short, long)]
+    verbose: bool,
+}
+
+/// Returns the package directories at or below `path`. A directory without a package manifest
+/// is searched for packages; tarballs are left out. Directories and tarballs that cannot be read
+/// are printed as warnings.
+fn get_package_paths(path: &Path, linter: &mut Linter) -> Vec<PathBuf> {
+    if path.join(PACKAGE_MANIFEST_FILENAME).is_file() {
+        return vec![path.to_path_buf()];
+    }
+
+    let mut package_paths = Vec::new();
+    for package_result in find_packages(path).checked() {
+        match package_result {
+            Ok(package) if package.source != PackageSource::Tarball => {
+                package_paths.push(package.path)
+            }
+            Ok(_) => {}
+            Err(error) => {
+                let package_path = match &error {
+                    DiscoveryError::Manifest(error) => error
+                        .path()
+                        .filter(|path| path.ends_with(PACKAGE_MANIFEST_FILENAME))
+                        .and_then(Path::parent),
+                    DiscoveryError::Walk(_) => None,
+                };
+                // Invalid manifests of package directories are reported when the package is
+                // checked.
+                match package_path {
+                    Some(package_path) => package_paths.push(package_path.to_path_buf()),
+                    None => print_discovery_warning(&error),
+                }
+            }
+        }
+    }
+
+    if package_paths.is_empty() {
+        linter.report(
+            Rule::PackageJson,
+            path,
+            format!("no {} found", PACKAGE_MANIFEST_FILENAME),
+        );
+    }
+    package_paths.sort_unstable();
+    package_paths
+}
+
+/// Returns `path` relative to the working directory `current_dir`, if it is inside it.
+fn get_display_path(path: &Path, current_dir: Option<&Path>) -> PathBuf {
+    current_dir
+        .and_then(|current_dir| path.strip_prefix(current_dir).ok())
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or(path)
+        .to_path_buf()
+}
+
+fn print_problems(problems: &[Problem]) {
+    let current_dir = env::current_dir()
+        .and_then(|current_dir| current_dir.canonicalize())
+        .ok();
+    for problem in problems.iter() {
+        println!(
+            "{}[{}]: {}: {}",
+            problem.severity,
+            problem.rule.name(),
+            get_display_path(problem.path.as_path(), current_dir.as_deref()).display(),
+            problem.message
+        );
+    }
+}
+
+/// Checks the packages and returns whether the check passed.
+fn run() -> Result<bool> {
+    let options = Options::parse();
+    let config = read_config(options.config.as_deref())?;
+    let mut linter = Linter::new(&config);
+
+    let mut package_paths = Vec::new();
+    for path in options.paths.iter() {
+        package_paths.extend(get_package_paths(Path::new(path), &mut linter));
+    }
+    for package_path in package_paths.iter() {
+        if options.verbose {
+            println!("checking {}", package_path.display());
+        }
+        linter.lint_package(package_path.as_path());
+    }
+
+    let problems = linter.problems;
+    print_problems(problems.as_slice());
+
+    let error_count = problems
+        .iter()
+        .filter(|problem| problem.severity == Severity::Error)
+        .count();
+    let warning_count = problems.len() - error_count;
+    println!(
+        "{} error(s), {} warning(s) in {} package(s)",
+        error_count,
+        warning_count,
+        package_paths.len()
+    );
+    Ok(error_count == 0 && (warning_count == 0 || !options.deny_warnings))
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(PROBLEMS_EXIT_CODE),
+        Err(error) => {
+            print_error(error.as_ref());
+            ExitCode::from(FAILURE_EXIT_CODE)
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use package_lib::{PACKAGE_MANIFEST_FILENAME, is_unity_ignored, read_json};
+use serde_json::Value;
+
+use crate::Result;
+use crate::config::{Config, Rule, Severity};
+
+/// Filename of the license of packages.
+const LICENSE_FILENAME: &str = "LICENSE.md";
+
+/// Filenames accepted as the readme of packages.
+const README_FILENAMES: [&str; 2] = ["README.md", "README"];
+
+/// Length package names must stay under, npm's limit.
+const MAX_NAME_LENGTH: usize = 214;
+
+/// Minimum number of dot-separated segments of package names, as in `com.company.package`.
+const MIN_NAME_SEGMENTS: usize = 3;
+
+/// Folders of packages that contain scripts, relative to the package directory. Each needs an
+/// assembly definition.
+const CODE_FOLDERS: [&str; 4] = ["Runtime", "Editor", "Tests/Runtime", "Tests/Editor"];
+
+/// Filename extension of C# scripts.
+const SCRIPT_EXTENSION: &str = "cs";
+
+/// Filename extension of assembly definitions.
+const ASSEMBLY_DEFINITION_EXTENSION: &str = "asmdef";
+
+/// Violation of a rule by a package.
+#[derive(Debug)]
+pub(crate) struct Problem {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// Path of the package, or of the file or folder in it the problem concerns.
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Collects the problems of packages at the severities of a config.
+pub(crate) struct Linter<'a> {
+    config: &'a Config,
+    pub problems: Vec<Problem>,
+}
+
+impl<'a> Linter<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Linter {
+            config,
+            problems: Vec::new(),
+        }
+    }
+
+    /// Adds a problem of `rule` concerning `path`, unless the rule is turned off.
+    pub fn report(&mut self, rule: Rule, path: &Path, message: String) {
+        let severity = self.config.severity(rule);
+        if severity == Severity::Off {
+            return;
+        }
+        self.problems.push(Problem {
+            rule,
+            severity,
+            path: path.to_path_buf(),
+            message,
+        });
+    }
+
+    /// Checks the package directory at `path` against all rules.
+    pub fn lint_package(&mut self, path: &Path) {
+        let manifest_path = path.join(PACKAGE_MANIFEST_FILENAME);
+        match read_json::<_, Value>(manifest_path.as_path()) {
+            Ok(package_json) => self.check_manifest(manifest_path.as_path(), &package_json),
+            Err(error) => self.report(
+                Rule::PackageJson,
+                manifest_path.as_path(),
+                error.to_string(),
+            ),
+        }
+        self.check_files(path);
+        self.check_scripts(path);
+    }
+
+    /// Checks that the package at `path` has a changelog, license and readme.
+    fn check_files(&mut self, path: &Path) {
+        let changelog_filename = self.config.changelog_filename.clone();
+        for (rule, filenames) in [
+            (Rule::Changelog, vec![changelog_filename.as_str()]),
+            (Rule::License, vec![LICENSE_FILENAME]),
+            (Rule::Readme, README_FILENAMES.to_vec()),
+        ] {
+            if !filenames
+                .iter()
+                .any(|filename| path.join(filename).is_file())
+            {
+                self.report(rule, path, format!("missing {}", filenames[0]));
+            }
+        }
+    }
+
+    /// Checks the `name` and `displayName` of the package manifest `package_json` read from the
+    /// file at `path`.
+    fn check_manifest(&mut self, path: &Path, package_json: &Value) {
+        match package_json["name"].as_str() {
+            Some(name) if name.len() >= MAX_NAME_LENGTH => self.report(
+                Rule::Name,
+                path,
+                format!(
+                    "name is {} characters long; it must be under {}",
+                    name.len(),
+                    MAX_NAME_LENGTH
+                ),
+            ),
+            Some(name) if !is_reverse_dns_name(name) => self.report(
+                Rule::Name,
+                path,
+                format!(
+                    "name '{}' is not a lowercase reverse-DNS name such as com.company.package",
+                    name
+                ),
+            ),
+            Some(_) => {}
+            None => self.report(Rule::Name, path, "missing name".to_owned()),
+        }
+
+        if package_json["displayName"]
+            .as_str()
+            .is_none_or(|display_name| display_name.trim().is_empty())
+        {
+            self.report(Rule::DisplayName, path, "missing displayName".to_owned());
+        }
+    }
+
+    /// Checks that the scripts of the package at `path` are in its code folders and that each
+    /// code folder with scripts has an assembly definition. If a folder cannot be read, that is
+    /// reported instead.
+    fn check_scripts(&mut self, path: &Path) {
+        let mut files = Vec::new();
+        if let Err(error) = collect_files(path, Path::new(""), &mut files) {
+            self.report(
+                Rule::FolderLayout,
+                path,
+                format!("scripts not checked: {}", error),
+            );
+            return;
+        }
+
+        let mut stray_folders = BTreeSet::new();
+        let mut code_folders = BTreeSet::new();
+        for file in files.iter() {
+            if !has_extension(file, SCRIPT_EXTENSION) {
+                continue;
+            }
+            match get_code_folder(file) {
+                Some(code_folder) => code_folders.insert(code_folder),
+                None => stray_folders.insert(file.parent().unwrap_or(Path::new("")).to_path_buf()),
+            };
+        }
+
+        for folder in stray_folders {
+            self.report(
+                Rule::FolderLayout,
+                path.join(folder).as_path(),
+                format!("scripts outside the {} folders", CODE_FOLDERS.join(", ")),
+            );
+        }
+        for code_folder in code_folders {
+            let has_assembly_definition = files.iter().any(|file| {
+                file.parent() == Some(Path::new(code_folder))
+                    && has_extension(file, ASSEMBLY_DEFINITION_EXTENSION)
+            });
+            if !has_assembly_definition {
+                self.report(
+                    Rule::AssemblyDefinition,
+                    path.join(code_folder).as_path(),
+                    "scripts without an assembly definition in the folder".to_owned(),
+                );
+            }
+        }
+    }
+}
+
+/// Returns whether `name` consists of at least three dot-separated segments of lowercase letters,
+/// digits, `-` and `_`, each starting with a letter or digit.
+fn is_reverse_dns_name(name: &str) -> bool {
+    let segments = name.split('.').collect::<Vec<_>>();
+    segments.len() >= MIN_NAME_SEGMENTS
+        && segments.iter().all(|segment| {
+            segment
+                .chars()
+                .next()
+                .is_some_and(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit())
+                && segment.chars().all(|ch| {
+                    ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-' || ch == '_'
+                })
+        })
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|ext| ext == extension)
+}
+
+/// Returns the code folder that contains the file at the package-relative `path`, if any.
+fn get_code_folder(path: &Path) -> Option<&'static str> {
+    CODE_FOLDERS
+        .iter()
+        .find(|code_folder| path.starts_with(code_folder))
+        .copied()
+}
+
+/// Adds the paths of the files in the directory `dir`, prefixed with `relative_path`, to `files`,
+/// skipping the directories Unity ignores.
+fn collect_files(dir: &Path, relative_path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir).map_err(|source| package_lib::Error::Io {
+        path: dir.to_path_buf(),
+        operation: "read",
+        source,
+    })?;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let path = relative_path.join(&name);
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !name.to_str().is_some_and(is_unity_ignored) {
+                collect_files(entry.path().as_path(), path.as_path(), files)?;
+            }
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tempfile::TempDir;
+
+    use super::{Linter, get_code_folder, is_reverse_dns_name};
+    use crate::common::create_fixture;
+    use crate::config::{Config, Rule};
+
+    /// Returns the rules and package-relative paths of the problems `linter` found in `package`.
+    fn get_problems(linter: &Linter, package: &TempDir) -> Vec<(Rule, String)> {
+        linter
+            .problems
+            .iter()
+            .map(|problem| {
+                let path = problem.path.strip_prefix(package.path()).unwrap();
+                (problem.rule, path.to_string_lossy().replace('\\', "/"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accepts_lowercase_reverse_dns_names() {
+        for name in [
+            "com.company.package",
+            "com.company.package.sub",
+            "io.a-b.c_d",
+            "com.2d.kit",
+        ] {
+            assert!(is_reverse_dns_name(name), "{}", name);
+        }
+        for name in [
+            "com.company",
+            "Com.company.package",
+            "com.company..package",
+            "com.company.-package",
+            "com.company.pack age",
+            "com.company.package.",
+        ] {
+            assert!(!is_reverse_dns_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn finds_code_folders_of_scripts() {
+        assert_eq!(get_code_folder(Path::new("Runtime/A.cs")), Some("Runtime"));
+        assert_eq!(
+            get_code_folder(Path::new("Tests/Editor/Sub/A.cs")),
+            Some("Tests/Editor")
+        );
+        assert_eq!(get_code_folder(Path::new("Tests/A.cs")), None);
+        assert_eq!(get_code_folder(Path::new("RuntimeExtra/A.cs")), None);
+        assert_eq!(get_code_folder(Path::new("A.cs")), None);
+    }
+
+    #[test]
+    fn reports_stray_scripts_and_missing_assembly_definitions() {
+        let package = create_fixture(&[
+            ("Runtime/A.cs", ""),
+            ("Runtime/Company.Package.asmdef", ""),
+            ("Editor/Sub/B.cs", ""),
+            ("Scripts/C.cs", ""),
+            ("Samples~/D.cs", ""),
+            ("Documentation/E.md", ""),
+        ]);
+        let config = Config::default();
+        let mut linter = Linter::new(&config);
+        linter.check_scripts(package.path());
+        assert_eq!(
+            get_problems(&linter, &package),
+            [
+                (Rule::FolderLayout, "Scripts".to_owned()),
+                (Rule::AssemblyDefinition, "Editor".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_files() {
+        let package = create_fixture(&[("release_notes.md", ""), ("README", "")]);
+        let config = Config::default();
+        let mut linter = Linter::new(&config);
+        linter.check_files(package.path());
+        assert_eq!(
+            get_problems(&linter, &package),
+            [
+                (Rule::Changelog, String::new()),
+                (Rule::License, String::new())
+            ]
+        );
+
+        let config = Config {
+            changelog_filename: "release_notes.md".to_owned(),
+            ..Config::default()
+        };
+        let mut linter = Linter::new(&config);
+        linter.check_files(package.path());
+        assert_eq!(
+            get_problems(&linter, &package),
+            [(Rule::License, String::new())]
+        );
+    }
+
+    #[test]
+    fn reports_unreadable_folders_and_continues() {
+        let package = create_fixture(&[("Runtime/A.cs", "")]);
+        let config = Config::default();
+        let mut linter = Linter::new(&config);
+        linter.check_scripts(package.path().join("Missing").as_path());
+        linter.check_scripts(package.path());
+        assert_eq!(
+            get_problems(&linter, &package),
+            [
+                (Rule::FolderLayout, "Missing".to_owned()),
+                (Rule::AssemblyDefinition, "Runtime".to_owned()),
+            ]
+        );
+        assert!(
+            linter.problems[0]
+                .message
+                .starts_with("scripts not checked: ")
+        );
+    }
+}
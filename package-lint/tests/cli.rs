@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+mod common;
+
+use common::create_fixture;
+
+/// Files of a package that follows all conventions, relative to the package directory.
+const VALID_PACKAGE: [(&str, &str); 6] = [
+    (
+        "package.json",
+        r#"{"name": "com.company.package", "version": "1.0.0", "displayName": "Package"}"#,
+    ),
+    ("CHANGELOG.md", ""),
+    ("LICENSE.md", ""),
+    ("README.md", ""),
+    ("Runtime/A.cs", ""),
+    ("Runtime/Company.Package.asmdef", ""),
+];
+
+/// Runs package-lint with `args` in the directory `dir`, so that no config file is read from
+/// elsewhere.
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_package-lint"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn passes_valid_packages() {
+    let fixture = create_fixture(&VALID_PACKAGE);
+    let output = run(fixture.path(), &[]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert!(stdout(&output).contains("0 error(s), 0 warning(s) in 1 package(s)"));
+}
+
+#[test]
+fn fails_on_warnings_only_if_denied() {
+    let fixture = create_fixture(&VALID_PACKAGE);
+    fs::remove_file(fixture.path().join("README.md")).unwrap();
+
+    let output = run(fixture.path(), &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("warning[readme]: "));
+
+    let output = run(fixture.path(), &["--deny-warnings"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn applies_config_severities() {
+    let fixture = create_fixture(&VALID_PACKAGE);
+    fs::remove_file(fixture.path().join("LICENSE.md")).unwrap();
+    fs::remove_file(fixture.path().join("README.md")).unwrap();
+
+    let output = run(fixture.path(), &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("error[license]: "));
+
+    fs::write(
+        fixture.path().join("package-lint.json"),
+        r#"{"rules": {"license": "off", "readme": "error"}}"#,
+    )
+    .unwrap();
+    let output = run(fixture.path(), &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!stdout(&output).contains("[license]"));
+    assert!(stdout(&output).contains("error[readme]: "));
+}
+
+#[test]
+fn fails_to_check_with_invalid_config() {
+    let fixture = create_fixture(&VALID_PACKAGE);
+    let output = run(fixture.path(), &["--config", "missing.json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: "));
+}
+
+#[test]
+fn reports_invalid_manifests_and_warns_about_unreadable_tarballs() {
+    let fixture = create_fixture(&[
+        ("Packages/a/package.json", "{"),
+        ("Packages/b.tgz", "not a tarball"),
+    ]);
+    let output = run(fixture.path(), &["Packages"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("error[package-json]: "));
+    assert!(stdout(&output).contains("in 1 package(s)"));
+    assert!(stderr(&output).contains("warning: "));
+    assert!(stderr(&output).contains("b.tgz"));
+}
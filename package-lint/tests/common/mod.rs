@@ -0,0 +1,24 @@
+//! Fixture helpers shared by the integration tests and, through a `#[path]` module, the unit
+//! tests. Each test uses only some of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+/// Writes `text` to the file at `path`, creating its parent directories.
+pub fn write_file(path: &Path, text: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, text).unwrap();
+}
+
+/// Creates a temporary directory with the files of `files`, given as paths relative to the
+/// directory along with their contents.
+pub fn create_fixture(files: &[(&str, &str)]) -> TempDir {
+    let fixture = tempfile::tempdir().unwrap();
+    for (path, text) in files.iter() {
+        write_file(fixture.path().join(path).as_path(), text);
+    }
+    fixture
+}
@@ -0,0 +1,165 @@
+use std::env;
+use std::error;
+use std::process::ExitCode;
+use std::result;
+
+use clap::{self, Parser};
+use package_lib::{
+    Manifest, PackContents, Registry, find_registry_token, get_npmrc_paths, get_pack_contents,
+    pack_package, print_error, read_json, write_package_tarball,
+};
+
+pub(crate) type Result<T> = result::Result<T, Box<dyn error::Error>>;
+
+#[derive(Parser, Debug)]
+#[clap(version, about, long_about = None)]
+pub(crate) struct Options {
+    /// Paths to the package directories to pack
+    #[clap(default_value = ".")]
+    pub package_paths: Vec<String>,
+    /// Directory to write the tarballs to
+    #[clap(short, long, default_value = ".")]
+    pub output_dir: String,
+    /// Publish the packages whose version is not in their registry yet instead of writing
+    /// tarballs
+    #[clap(long)]
+    pub publish: bool,
+    /// Path to the manifest file whose scoped registries the packages are published to
+    #[clap(short, long, default_value = "Packages/manifest.json")]
+    pub manifest_path: String,
+    /// Environment variable to read the token of the registries published to from, such as
+    /// NPM_TOKEN; defaults to the registry tokens in .npmrc files
+    #[clap(long, value_name = "NAME")]
+    pub registry_token_env: Option<String>,
+    /// List the files that would be packed without writing tarballs, or the packages that would
+    /// be published without publishing them
+    #[clap(long)]
+    pub dry_run: bool,
+    /// Verbose output
+    #[clap(short, long)]
+    verbose: bool,
+}
+
+fn print_contents(contents: &PackContents) {
+    println!(
+        "{} {}: {} file(s)",
+        contents.name,
+        contents.version,
+        contents.files.len()
+    );
+    for file in contents.files.iter() {
+        println!("  {}", file.display());
+    }
+}
+
+fn pack(options: &Options) -> Result<()> {
+    for package_path in options.package_paths.iter() {
+        let (contents, tarball_path) = if options.dry_run {
+            let contents = get_pack_contents(package_path)?;
+            let tarball_path = contents.tarball_filename().into();
+            (contents, tarball_path)
+        } else {
+            pack_package(package_path, options.output_dir.as_str())?
+        };
+
+        if options.verbose || options.dry_run {
+            print_contents(&contents);
+        }
+        println!("{}", tarball_path.display());
+    }
+
+    Ok(())
+}
+
+/// Returns the token given by the environment variable named `name`, if a name is given.
+fn read_token_variable(name: Option<&str>) -> Result<Option<String>> {
+    let Some(name) = name else {
+        return Ok(None);
+    };
+    match env::var(name) {
+        Ok(token) if !token.is_empty() => Ok(Some(token)),
+        _ => Err(format!("environment variable {} is not set", name).into()),
+    }
+}
+
+/// Publishes each package to the scoped registry of the manifest whose scope matches it, unless
+/// the registry already has the version.
+fn publish(options: &Options) -> Result<()> {
+    let manifest: Manifest = read_json(options.manifest_path.as_str())?;
+    let token = read_token_variable(options.registry_token_env.as_deref())?;
+    let npmrc_paths = get_npmrc_paths();
+    let mut published_count = 0;
+    let mut skipped_count = 0;
+
+    for package_path in options.package_paths.iter() {
+        let contents = get_pack_contents(package_path)?;
+        let Some(scoped_registry) = manifest.get_scoped_registry(contents.name.as_str()) else {
+            return Err(format!(
+                "no scoped registry in '{}' has a scope matching {}",
+                options.manifest_path, contents.name
+            )
+            .into());
+        };
+        let url = scoped_registry.url.as_str();
+        let token = token
+            .clone()
+            .or_else(|| find_registry_token(url, npmrc_paths.as_slice()));
+        let registry = Registry::new(url, token);
+
+        let version = contents.version.to_string();
+        if registry
+            .get_versions(contents.name.as_str())?
+            .contains(&version)
+        {
+            println!(
+                "skipped {} {}: already in {}",
+                contents.name, version, scoped_registry.name
+            );
+            skipped_count += 1;
+            continue;
+        }
+
+        if options.verbose {
+            print_contents(&contents);
+        }
+        if options.dry_run {
+            println!(
+                "would publish {} {} to {}",
+                contents.name, version, scoped_registry.name
+            );
+        } else {
+            let tarball = write_package_tarball(package_path, &contents, Vec::new())?;
+            registry.publish(package_path, &contents, tarball.as_slice())?;
+            println!(
+                "published {} {} to {}",
+                contents.name, version, scoped_registry.name
+            );
+        }
+        published_count += 1;
+    }
+
+    println!(
+        "{} package(s) published, {} skipped",
+        published_count, skipped_count
+    );
+    Ok(())
+}
+
+fn run() -> Result<()> {
+    let options = Options::parse();
+    if options.publish {
+        publish(&options)
+    } else {
+        pack(&options)
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            print_error(error.as_ref());
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::release::VersionBump;
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub(crate) enum CommandKind {
     UpdateMajor,
@@ -5,78 +7,224 @@ pub(crate) enum CommandKind {
     UpdatePatch,
     Skip,
     Diff,
+    DiffMode,
+    DiffUntracked,
+    DiffFilter,
+    DiffWords,
+    DiffSideBySide,
+    Back,
+    Goto,
+    UpdateAll,
+    Plan,
     Quit,
     Help,
 }
 
+impl CommandKind {
+    /// Returns the version part updated by the command, if it is an update command.
+    pub fn version_bump(self) -> Option<VersionBump> {
+        match self {
+            CommandKind::UpdateMajor => Some(VersionBump::Major),
+            CommandKind::UpdateMinor => Some(VersionBump::Minor),
+            CommandKind::UpdatePatch => Some(VersionBump::Patch),
+            _ => None,
+        }
+    }
+}
+
+/// Command entered at the prompt along with its argument.
+pub(crate) struct Command<'a> {
+    pub kind: CommandKind,
+    pub argument: Option<&'a str>,
+}
+
 struct CommandMeta {
     pub kind: CommandKind,
     pub key: char,
+    pub name: &'static str,
+    pub argument: Option<&'static str>,
     pub help: &'static str,
 }
 
-const COMMAND_LIST: [CommandMeta; 7] = [
+const COMMAND_LIST: [CommandMeta; 16] = [
     CommandMeta {
         kind: CommandKind::UpdateMajor,
         key: '1',
+        name: "major",
+        argument: None,
         help: "update package major version",
     },
     CommandMeta {
         kind: CommandKind::UpdateMinor,
         key: '2',
+        name: "minor",
+        argument: None,
         help: "update package minor version",
     },
     CommandMeta {
         kind: CommandKind::UpdatePatch,
         key: '3',
+        name: "patch",
+        argument: None,
         help: "update package patch version",
     },
     CommandMeta {
         kind: CommandKind::Skip,
         key: 's',
+        name: "skip",
+        argument: None,
         help: "skip current package",
     },
     CommandMeta {
         kind: CommandKind::Diff,
         key: 'd',
+        name: "diff",
+        argument: None,
         help: "show diff for current package",
     },
+    CommandMeta {
+        kind: CommandKind::DiffMode,
+        key: 'm',
+        name: "mode",
+        argument: Some("<staged|unstaged|all>"),
+        help: "set which changes the diff shows",
+    },
+    CommandMeta {
+        kind: CommandKind::DiffUntracked,
+        key: 'u',
+        name: "untracked",
+        argument: None,
+        help: "toggle showing untracked files in the diff",
+    },
+    CommandMeta {
+        kind: CommandKind::DiffFilter,
+        key: 'f',
+        name: "filter",
+        argument: Some("<meta|yaml|summary>"),
+        help: "toggle a filter of the diff",
+    },
+    CommandMeta {
+        kind: CommandKind::DiffWords,
+        key: 'w',
+        name: "words",
+        argument: None,
+        help: "toggle highlighting changed words in the diff",
+    },
+    CommandMeta {
+        kind: CommandKind::DiffSideBySide,
+        key: 'v',
+        name: "side",
+        argument: None,
+        help: "toggle showing the diff side by side",
+    },
+    CommandMeta {
+        kind: CommandKind::Back,
+        key: 'b',
+        name: "back",
+        argument: None,
+        help: "go back to the previous package",
+    },
+    CommandMeta {
+        kind: CommandKind::Goto,
+        key: 'g',
+        name: "goto",
+        argument: Some("<name|index>"),
+        help: "go to the package with the given name or index",
+    },
+    CommandMeta {
+        kind: CommandKind::UpdateAll,
+        key: 'a',
+        name: "all",
+        argument: Some("<1|2|3>"),
+        help: "update current and all remaining packages with the same version part and message",
+    },
+    CommandMeta {
+        kind: CommandKind::Plan,
+        key: 'p',
+        name: "plan",
+        argument: None,
+        help: "show the planned updates",
+    },
     CommandMeta {
         kind: CommandKind::Quit,
         key: 'q',
-        help: "quit; do not update package or any of the remaining ones",
+        name: "quit",
+        argument: None,
+        help: "quit; apply the planned updates and skip all undecided packages",
     },
     CommandMeta {
         kind: CommandKind::Help,
         key: '?',
+        name: "help",
+        argument: None,
         help: "print help",
     },
 ];
 
-pub(crate) fn get_command_kind_from_input(input: &str) -> Option<CommandKind> {
-    let mut chars = input.chars();
+/// Parses a command from a single key or command name followed by the argument, if the command
+/// takes one.
+pub(crate) fn get_command_from_input(input: &str) -> Option<Command<'_>> {
+    let input = input.trim();
+    let (word, argument) = match input.split_once(char::is_whitespace) {
+        Some((word, argument)) => (word, Some(argument.trim())),
+        None => (input, None),
+    };
+
+    let mut chars = word.chars();
     let key = chars.next()?;
-    if chars.next().is_some() {
+    let is_key = chars.next().is_none();
+
+    let command = COMMAND_LIST.iter().find(|&command| {
+        if is_key {
+            command.key == key
+        } else {
+            command.name == word
+        }
+    })?;
+    if command.argument.is_some() != argument.is_some() {
         return None;
     }
+
+    Some(Command {
+        kind: command.kind,
+        argument,
+    })
+}
+
+/// Parses the version part argument of a command, either as the key or name of an update command.
+pub(crate) fn get_version_bump_from_argument(argument: &str) -> Option<VersionBump> {
+    get_command_from_input(argument)?.kind.version_bump()
+}
+
+pub(crate) fn get_command_keys() -> Vec<String> {
     COMMAND_LIST
         .iter()
-        .find(|&command| command.key == key)
-        .map(|command| command.kind)
+        .map(|command| command.key.to_string())
+        .collect()
 }
 
-pub(crate) fn get_command_key_text() -> String {
+pub(crate) fn get_command_names() -> Vec<String> {
     COMMAND_LIST
         .iter()
-        .map(|command| command.key.to_string())
-        .collect::<Vec<String>>()
-        .join(",")
+        .map(|command| command.name.to_owned())
+        .collect()
+}
+
+pub(crate) fn get_command_key_text() -> String {
+    get_command_keys().join(",")
 }
 
 pub(crate) fn get_command_help() -> String {
     COMMAND_LIST
         .iter()
-        .map(|command| format!("{} - {}", command.key, command.help))
+        .map(|command| {
+            let usage = match command.argument {
+                Some(argument) => format!("{}|{} {}", command.key, command.name, argument),
+                None => format!("{}|{}", command.key, command.name),
+            };
+            format!("{} - {}", usage, command.help)
+        })
+        .chain(["Ctrl-C|Ctrl-D - abort without applying any updates".to_owned()])
         .collect::<Vec<String>>()
         .join("\n")
 }
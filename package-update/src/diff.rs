@@ -1,29 +1,317 @@
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::str;
 
+use clap::ValueEnum;
 use git2::Repository;
 use owo_colors::OwoColorize;
 
-use package_lib::Result;
-
 use crate::Package;
+use crate::Result;
+use crate::pager::page_text;
+
+pub(crate) use filter::{DiffFilter, DiffFilters};
+pub(crate) use render::RenderOptions;
+#[cfg(feature = "tui")]
+pub(crate) use render::{RenderedLine, render_lines};
+
+mod filter;
+mod render;
+
+/// Output width used when the terminal width is not known.
+const DEFAULT_WIDTH: usize = 120;
+
+/// States of the package files compared by a diff.
+#[derive(ValueEnum, PartialEq, Eq, Clone, Copy, Debug)]
+pub(crate) enum DiffMode {
+    /// Changes between HEAD and the index
+    Staged,
+    /// Changes between the index and the working tree
+    Unstaged,
+    /// Changes between HEAD and the working tree
+    All,
+}
+
+impl DiffMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            DiffMode::Staged => "staged",
+            DiffMode::Unstaged => "unstaged",
+            DiffMode::All => "all",
+        }
+    }
+
+    fn source(self) -> DiffSource {
+        match self {
+            DiffMode::Staged => DiffSource::Staged,
+            DiffMode::Unstaged => DiffSource::Unstaged,
+            DiffMode::All => DiffSource::All,
+        }
+    }
+}
+
+/// Settings of the package diffs, switchable from the prompt.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DiffSettings {
+    pub mode: DiffMode,
+    pub include_untracked: bool,
+    pub word_diff: bool,
+    pub side_by_side: bool,
+    pub filters: DiffFilters,
+}
+
+impl DiffSettings {
+    /// Returns the render options for output of `width` columns.
+    pub fn render_options(&self, width: usize) -> RenderOptions {
+        RenderOptions {
+            word_diff: self.word_diff,
+            side_by_side: self.side_by_side,
+            width,
+        }
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+impl fmt::Display for DiffSettings {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "diff mode: {}, untracked files {}, word diff {}, side by side {}, filters: {}",
+            self.mode.name(),
+            if self.include_untracked {
+                "included"
+            } else {
+                "excluded"
+            },
+            on_off(self.word_diff),
+            on_off(self.side_by_side),
+            self.filters
+        )
+    }
+}
+
+/// Source of the changes of a hunk.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub(crate) enum DiffSource {
+    Staged,
+    Unstaged,
+    All,
+    Untracked,
+}
+
+impl DiffSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            DiffSource::Staged => "staged",
+            DiffSource::Unstaged => "unstaged",
+            DiffSource::All => "staged+unstaged",
+            DiffSource::Untracked => "untracked",
+        }
+    }
+}
+
+/// Line of diff output.
+pub(crate) enum DiffLine {
+    FileHeader {
+        old_path: String,
+        new_path: String,
+    },
+    HunkHeader {
+        lines: String,
+        context: String,
+        source: Option<DiffSource>,
+    },
+    Content {
+        origin: char,
+        content: String,
+    },
+    Binary {
+        old_file: Option<BinaryFile>,
+        new_file: Option<BinaryFile>,
+    },
+    /// Text added by a diff filter in place of hidden lines.
+    Note(String),
+}
+
+/// Size and object id of one side of a binary file change.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BinaryFile {
+    pub size: u64,
+    pub id: git2::Oid,
+}
+
+impl BinaryFile {
+    /// Returns the size and id of `file` or `None` if it does not exist. Ids that git has not
+    /// computed for files in the working tree are hashed from the file in `workdir`.
+    fn from_diff_file(file: &git2::DiffFile, workdir: Option<&Path>) -> Result<Option<Self>> {
+        if !file.exists() {
+            return Ok(None);
+        }
+
+        let mut binary_file = BinaryFile {
+            size: file.size(),
+            id: file.id(),
+        };
+        if file.id().is_zero()
+            && let (Some(workdir), Some(path)) = (workdir, file.path())
+        {
+            let path = workdir.join(path);
+            binary_file.size = fs::metadata(path.as_path())?.len();
+            binary_file.id = git2::Oid::hash_file(git2::ObjectType::Blob, path)?;
+        }
+        Ok(Some(binary_file))
+    }
+}
+
+impl fmt::Display for BinaryFile {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let id = self.id.to_string();
+        write!(formatter, "{} ({})", format_size(self.size), &id[..7])
+    }
+}
+
+/// Formats a number of bytes with a binary unit.
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next_unit in UNITS.iter().skip(1) {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next_unit;
+    }
+    format!("{:.1} {}", value, unit)
+}
 
-fn diff_file_cb(delta: git2::DiffDelta, _progress: f32) -> bool {
-    let old_file = delta.old_file().path().map_or("", |x| x.to_str().unwrap());
-    let new_file = delta.new_file().path().map_or("", |x| x.to_str().unwrap());
-    let text = format!("--- a/{}\n+++ b/{}\n", old_file, new_file);
-    print!("{}", text.bold());
-    true
+/// Returns a description of a binary file change with the size and id of both sides.
+pub(crate) fn get_binary_change_text(
+    old_file: Option<&BinaryFile>,
+    new_file: Option<&BinaryFile>,
+) -> String {
+    match (old_file, new_file) {
+        (None, Some(new_file)) => format!("binary file added: {}", new_file),
+        (Some(old_file), None) => format!("binary file deleted: {}", old_file),
+        (Some(old_file), Some(new_file)) => {
+            let (sign, size_delta) = if new_file.size >= old_file.size {
+                ('+', new_file.size - old_file.size)
+            } else {
+                ('-', old_file.size - new_file.size)
+            };
+            format!(
+                "binary file changed: {} -> {}, {}{}",
+                old_file,
+                new_file,
+                sign,
+                format_size(size_delta)
+            )
+        }
+        (None, None) => String::from("Binary files differ"),
+    }
 }
 
-fn diff_binary_cb(_delta: git2::DiffDelta, _binary: git2::DiffBinary) -> bool {
-    println!("Binary files differ");
-    true
+/// Decodes text as UTF-8, or as Latin-1 if it is not valid UTF-8.
+fn decode_text(bytes: &[u8]) -> String {
+    match str::from_utf8(bytes) {
+        Ok(text) => text.to_owned(),
+        Err(_) => bytes.iter().map(|&byte| char::from(byte)).collect(),
+    }
+}
+
+impl DiffLine {
+    fn from_delta(delta: &git2::DiffDelta) -> Self {
+        let get_path = |file: git2::DiffFile| {
+            file.path()
+                .map_or(String::new(), |path| path.to_string_lossy().into_owned())
+        };
+        DiffLine::FileHeader {
+            old_path: get_path(delta.old_file()),
+            new_path: get_path(delta.new_file()),
+        }
+    }
+
+    fn from_binary(delta: &git2::DiffDelta, workdir: Option<&Path>) -> Result<Self> {
+        Ok(DiffLine::Binary {
+            old_file: BinaryFile::from_diff_file(&delta.old_file(), None)?,
+            new_file: BinaryFile::from_diff_file(&delta.new_file(), workdir)?,
+        })
+    }
+
+    fn from_hunk(hunk: &git2::DiffHunk, source: Option<DiffSource>) -> Self {
+        let header = decode_text(hunk.header());
+        let (lines, context) = split_header(header.as_str());
+        DiffLine::HunkHeader {
+            lines: lines.to_owned(),
+            context: context.to_owned(),
+            source,
+        }
+    }
+
+    fn from_line(line: &git2::DiffLine) -> Self {
+        DiffLine::Content {
+            origin: line.origin(),
+            content: decode_text(line.content()),
+        }
+    }
+}
+
+impl fmt::Display for DiffLine {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiffLine::FileHeader { old_path, new_path } => {
+                let text = format!("--- a/{}\n+++ b/{}\n", old_path, new_path);
+                write!(formatter, "{}", text.bold())
+            }
+            DiffLine::HunkHeader {
+                lines,
+                context,
+                source,
+            } => match source {
+                Some(source) => {
+                    let label = format!("[{}]", source.name());
+                    writeln!(
+                        formatter,
+                        "{} {} {}",
+                        lines.bright_cyan(),
+                        label.magenta(),
+                        context
+                    )
+                }
+                None => writeln!(formatter, "{} {}", lines.bright_cyan(), context),
+            },
+            DiffLine::Content { origin, content } => {
+                if *origin == '+' {
+                    write!(formatter, "{} {}", origin.green(), content.green())
+                } else if *origin == '-' {
+                    write!(formatter, "{} {}", origin.red(), content.red())
+                } else {
+                    write!(formatter, "{} {}", origin, content)
+                }
+            }
+            DiffLine::Binary { old_file, new_file } => writeln!(
+                formatter,
+                "{}",
+                get_binary_change_text(old_file.as_ref(), new_file.as_ref())
+            ),
+            DiffLine::Note(text) => writeln!(formatter, "{}", text.italic().cyan()),
+        }
+    }
 }
 
 fn split_header(header: &str) -> (&str, &str) {
     let mut lines_end = 0;
     let mut count = 0;
-    for (index, ch) in header.chars().enumerate() {
+    for (index, ch) in header.char_indices() {
         if ch != '@' {
             continue;
         }
@@ -36,45 +324,297 @@ fn split_header(header: &str) -> (&str, &str) {
     (header[0..lines_end].trim(), header[lines_end..].trim())
 }
 
-fn git_diff_hunk_cb(_delta: git2::DiffDelta, hunk: git2::DiffHunk) -> bool {
-    let header = str::from_utf8(hunk.header()).unwrap();
-    let (lines, context) = split_header(header);
-    println!("{} {}", lines.bright_cyan(), context);
-    true
+/// Returns the width of the terminal, falling back to `$COLUMNS` or a default width.
+fn get_output_width() -> usize {
+    if let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() {
+        return width as usize;
+    }
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(DEFAULT_WIDTH)
 }
 
-fn diff_line_cb(
-    _delta: git2::DiffDelta,
-    _hunk: Option<git2::DiffHunk>,
-    line: git2::DiffLine,
-) -> bool {
-    let origin = line.origin();
-    let content = str::from_utf8(line.content()).unwrap();
-    if origin == '+' {
-        print!("{} {}", origin.green(), content.green());
-    } else if origin == '-' {
-        print!("{} {}", origin.red(), content.red());
-    } else {
-        print!("{} {}", origin, content);
+/// Appends the lines of `diff` to `diff_lines`, labelling hunks with `source`. If `untracked_only`
+/// is set, only untracked files are included. Binary files in the working tree are read from
+/// `workdir`.
+fn collect_diff_lines(
+    diff: &git2::Diff,
+    source: DiffSource,
+    untracked_only: bool,
+    workdir: Option<&Path>,
+    diff_lines: &mut Vec<DiffLine>,
+) -> Result<()> {
+    let is_included =
+        |delta: &git2::DiffDelta| !untracked_only || delta.status() == git2::Delta::Untracked;
+    let get_source = |delta: &git2::DiffDelta| {
+        if delta.status() == git2::Delta::Untracked {
+            DiffSource::Untracked
+        } else {
+            source
+        }
+    };
+
+    let diff_lines = RefCell::new(diff_lines);
+    // Errors cannot be returned from the callbacks, so the first one is kept and the diff aborted.
+    let callback_error = RefCell::new(None);
+    let result = diff.foreach(
+        &mut |delta, _progress| {
+            if is_included(&delta) {
+                diff_lines.borrow_mut().push(DiffLine::from_delta(&delta));
+            }
+            true
+        },
+        Some(&mut |delta, _binary| {
+            if !is_included(&delta) {
+                return true;
+            }
+            match DiffLine::from_binary(&delta, workdir) {
+                Ok(diff_line) => {
+                    diff_lines.borrow_mut().push(diff_line);
+                    true
+                }
+                Err(error) => {
+                    *callback_error.borrow_mut() = Some(error);
+                    false
+                }
+            }
+        }),
+        Some(&mut |delta, hunk| {
+            if is_included(&delta) {
+                let hunk_source = get_source(&delta);
+                diff_lines
+                    .borrow_mut()
+                    .push(DiffLine::from_hunk(&hunk, Some(hunk_source)));
+            }
+            true
+        }),
+        Some(&mut |delta, _hunk, line| {
+            if is_included(&delta) {
+                diff_lines.borrow_mut().push(DiffLine::from_line(&line));
+            }
+            true
+        }),
+    );
+
+    if let Some(error) = callback_error.into_inner() {
+        return Err(error);
     }
-    true
+    result?;
+
+    Ok(())
+}
+
+/// Diff of package files along with the source of its changes.
+struct SourceDiff<'repo> {
+    diff: git2::Diff<'repo>,
+    source: DiffSource,
+    /// Whether only the untracked files of the diff belong to the source.
+    untracked_only: bool,
+}
+
+/// Opens the repository of the submodule containing `package`, if it is in a submodule.
+fn open_submodule_repo(repo: &Repository, package: &Package) -> Result<Option<Repository>> {
+    let (Some(submodule_path), Some(workdir_path)) = (&package.submodule_path, repo.workdir())
+    else {
+        return Ok(None);
+    };
+    Ok(Some(Repository::open(workdir_path.join(submodule_path))?))
 }
 
-pub(crate) fn print_diff(repo: &Repository, package: &Package) -> Result<()> {
+/// Returns the diffs of the files of `package` for the mode in `settings`. `repo` is the
+/// repository of the submodule for packages in submodules. If `split_sources` is set, the changes
+/// between HEAD and the working tree are returned as separate staged and unstaged diffs, so that
+/// each hunk has a single source.
+fn get_source_diffs<'repo>(
+    repo: &'repo Repository,
+    package: &Package,
+    settings: &DiffSettings,
+    split_sources: bool,
+) -> Result<Vec<SourceDiff<'repo>>> {
+    // Paths of packages in submodules are relative to the submodule's repository.
+    let pathspec = match &package.submodule_path {
+        Some(submodule_path) => package.path.strip_prefix(submodule_path)?,
+        None => package.path.as_path(),
+    };
+
     let mut diff_options = git2::DiffOptions::new();
     diff_options
-        .pathspec(package.path.as_path())
-        .ignore_whitespace(true);
+        .pathspec(pathspec)
+        .ignore_whitespace(true)
+        .include_untracked(settings.include_untracked)
+        .recurse_untracked_dirs(settings.include_untracked)
+        .show_untracked_content(settings.include_untracked);
 
-    let head = repo.head()?.peel_to_tree()?;
-    let diff = repo.diff_tree_to_index(Some(&head), None, Some(&mut diff_options))?;
+    let source = settings.mode.source();
+    let mut source_diffs = Vec::new();
 
-    diff.foreach(
-        &mut diff_file_cb,
-        Some(&mut diff_binary_cb),
-        Some(&mut git_diff_hunk_cb),
-        Some(&mut diff_line_cb),
+    match settings.mode {
+        DiffMode::Staged => {
+            let head = repo.head()?.peel_to_tree()?;
+            source_diffs.push(SourceDiff {
+                diff: repo.diff_tree_to_index(Some(&head), None, Some(&mut diff_options))?,
+                source,
+                untracked_only: false,
+            });
+
+            // Untracked files are not part of the index, so they are taken from the working tree.
+            if settings.include_untracked {
+                source_diffs.push(SourceDiff {
+                    diff: repo.diff_index_to_workdir(None, Some(&mut diff_options))?,
+                    source,
+                    untracked_only: true,
+                });
+            }
+        }
+        DiffMode::Unstaged => {
+            source_diffs.push(SourceDiff {
+                diff: repo.diff_index_to_workdir(None, Some(&mut diff_options))?,
+                source,
+                untracked_only: false,
+            });
+        }
+        DiffMode::All if split_sources => {
+            let head = repo.head()?.peel_to_tree()?;
+            source_diffs.push(SourceDiff {
+                diff: repo.diff_tree_to_index(Some(&head), None, Some(&mut diff_options))?,
+                source: DiffSource::Staged,
+                untracked_only: false,
+            });
+            source_diffs.push(SourceDiff {
+                diff: repo.diff_index_to_workdir(None, Some(&mut diff_options))?,
+                source: DiffSource::Unstaged,
+                untracked_only: false,
+            });
+        }
+        DiffMode::All => {
+            let head = repo.head()?.peel_to_tree()?;
+            source_diffs.push(SourceDiff {
+                diff: repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut diff_options))?,
+                source,
+                untracked_only: false,
+            });
+        }
+    }
+
+    Ok(source_diffs)
+}
+
+/// Returns the diff of the files of `package` for the mode in `settings`.
+pub(crate) fn get_diff(
+    repo: &Repository,
+    package: &Package,
+    settings: &DiffSettings,
+) -> Result<Vec<DiffLine>> {
+    let submodule_repo = open_submodule_repo(repo, package)?;
+    let repo = submodule_repo.as_ref().unwrap_or(repo);
+
+    let mut diff_lines = Vec::<DiffLine>::new();
+    for source_diff in get_source_diffs(repo, package, settings, true)? {
+        collect_diff_lines(
+            &source_diff.diff,
+            source_diff.source,
+            source_diff.untracked_only,
+            repo.workdir(),
+            &mut diff_lines,
+        )?;
+    }
+    Ok(filter::filter_diff_lines(diff_lines, &settings.filters))
+}
+
+/// Number of changed files and lines of a package diff.
+#[derive(Default, Clone, Copy, Debug)]
+pub(crate) struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl fmt::Display for DiffStat {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)",
+            self.files_changed,
+            self.insertions.green(),
+            self.deletions.red()
+        )
+    }
+}
+
+/// Returns the number of changed files and lines of `package` for the mode in `settings`.
+pub(crate) fn get_diff_stat(
+    repo: &Repository,
+    package: &Package,
+    settings: &DiffSettings,
+) -> Result<DiffStat> {
+    let submodule_repo = open_submodule_repo(repo, package)?;
+    let repo = submodule_repo.as_ref().unwrap_or(repo);
+
+    let mut diff_stat = DiffStat::default();
+    for source_diff in get_source_diffs(repo, package, settings, false)? {
+        if !source_diff.untracked_only {
+            let stats = source_diff.diff.stats()?;
+            diff_stat.files_changed += stats.files_changed();
+            diff_stat.insertions += stats.insertions();
+            diff_stat.deletions += stats.deletions();
+            continue;
+        }
+
+        // The stats of the whole diff would include unstaged changes of tracked files.
+        for (index, delta) in source_diff.diff.deltas().enumerate() {
+            if delta.status() != git2::Delta::Untracked {
+                continue;
+            }
+            diff_stat.files_changed += 1;
+            if let Some(patch) = git2::Patch::from_diff(&source_diff.diff, index)? {
+                let (_context, insertions, deletions) = patch.line_stats()?;
+                diff_stat.insertions += insertions;
+                diff_stat.deletions += deletions;
+            }
+        }
+    }
+
+    Ok(diff_stat)
+}
+
+/// Shows the diff of `package` in the user's pager.
+pub(crate) fn print_diff(
+    repo: &Repository,
+    package: &Package,
+    settings: &DiffSettings,
+) -> Result<()> {
+    let diff_lines = get_diff(repo, package, settings)?;
+    let options = settings.render_options(get_output_width());
+    page_text(render::render(&diff_lines, &options).as_str())
+}
+
+/// Prints the diff between `old_text` and `new_text` of the file at `path`.
+pub(crate) fn print_text_diff(path: &Path, old_text: &str, new_text: &str) -> Result<()> {
+    let patch = git2::Patch::from_buffers(
+        old_text.as_bytes(),
+        Some(path),
+        new_text.as_bytes(),
+        Some(path),
+        None,
     )?;
 
+    let mut diff_lines = vec![DiffLine::from_delta(&patch.delta())];
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_index)?;
+        diff_lines.push(DiffLine::from_hunk(&hunk, None));
+        for line_index in 0..line_count {
+            diff_lines.push(DiffLine::from_line(
+                &patch.line_in_hunk(hunk_index, line_index)?,
+            ));
+        }
+    }
+    let options = RenderOptions {
+        word_diff: true,
+        side_by_side: false,
+        width: get_output_width(),
+    };
+    print!("{}", render::render(&diff_lines, &options));
+
     Ok(())
 }
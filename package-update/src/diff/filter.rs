@@ -0,0 +1,581 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use super::{DiffLine, get_binary_change_text};
+
+/// Extensions of files Unity serializes as YAML.
+const UNITY_YAML_EXTENSIONS: [&str; 20] = [
+    "anim",
+    "asset",
+    "brush",
+    "controller",
+    "cubemap",
+    "flare",
+    "fontsettings",
+    "guiskin",
+    "lighting",
+    "mask",
+    "mat",
+    "mixer",
+    "overrideController",
+    "physicMaterial",
+    "playable",
+    "prefab",
+    "preset",
+    "renderTexture",
+    "spriteatlas",
+    "unity",
+];
+
+/// Properties whose changes are serialization noise.
+const NOISE_PROPERTIES: [&str; 1] = ["m_ObjectHideFlags"];
+
+/// Start of the header line of a Unity YAML document.
+const DOCUMENT_HEADER_PREFIX: &str = "--- !u!";
+
+/// Filter of package diffs, toggleable from the prompt.
+#[derive(ValueEnum, PartialEq, Eq, Clone, Copy, Debug)]
+pub(crate) enum DiffFilter {
+    /// Drop all changes of .meta files, including GUID changes
+    Meta,
+    /// Hide Unity YAML noise and fold unchanged YAML documents
+    Yaml,
+    /// Summarise changes of Unity assets instead of showing their hunks
+    Summary,
+}
+
+impl DiffFilter {
+    pub fn name(self) -> &'static str {
+        match self {
+            DiffFilter::Meta => "meta",
+            DiffFilter::Yaml => "yaml",
+            DiffFilter::Summary => "summary",
+        }
+    }
+}
+
+/// Enabled diff filters.
+#[derive(Default, Clone, Copy, Debug)]
+pub(crate) struct DiffFilters {
+    pub drop_meta: bool,
+    pub fold_yaml: bool,
+    pub summarize_assets: bool,
+}
+
+impl DiffFilters {
+    pub fn new(filters: &[DiffFilter]) -> Self {
+        let mut diff_filters = Self::default();
+        for &filter in filters.iter() {
+            *diff_filters.get_mut(filter) = true;
+        }
+        diff_filters
+    }
+
+    fn get_mut(&mut self, filter: DiffFilter) -> &mut bool {
+        match filter {
+            DiffFilter::Meta => &mut self.drop_meta,
+            DiffFilter::Yaml => &mut self.fold_yaml,
+            DiffFilter::Summary => &mut self.summarize_assets,
+        }
+    }
+
+    pub fn is_enabled(&self, filter: DiffFilter) -> bool {
+        match filter {
+            DiffFilter::Meta => self.drop_meta,
+            DiffFilter::Yaml => self.fold_yaml,
+            DiffFilter::Summary => self.summarize_assets,
+        }
+    }
+
+    pub fn toggle(&mut self, filter: DiffFilter) {
+        let enabled = self.get_mut(filter);
+        *enabled = !*enabled;
+    }
+}
+
+impl fmt::Display for DiffFilters {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let names = DiffFilter::value_variants()
+            .iter()
+            .filter(|&&filter| self.is_enabled(filter))
+            .map(|filter| filter.name())
+            .collect::<Vec<&str>>();
+        if names.is_empty() {
+            write!(formatter, "none")
+        } else {
+            write!(formatter, "{}", names.join(","))
+        }
+    }
+}
+
+/// Hunk header and the lines of the hunk.
+struct Hunk {
+    header: DiffLine,
+    lines: Vec<DiffLine>,
+}
+
+/// Diff lines of a single file.
+struct FileDiff {
+    header: Option<DiffLine>,
+    /// Lines of the file that are not part of a hunk.
+    lines: Vec<DiffLine>,
+    hunks: Vec<Hunk>,
+}
+
+impl FileDiff {
+    fn path(&self) -> &str {
+        match &self.header {
+            Some(DiffLine::FileHeader { old_path, new_path }) if new_path.is_empty() => old_path,
+            Some(DiffLine::FileHeader { new_path, .. }) => new_path,
+            _ => "",
+        }
+    }
+
+    fn into_lines(self) -> Vec<DiffLine> {
+        let mut diff_lines = Vec::new();
+        diff_lines.extend(self.header);
+        diff_lines.extend(self.lines);
+        for hunk in self.hunks {
+            diff_lines.push(hunk.header);
+            diff_lines.extend(hunk.lines);
+        }
+        diff_lines
+    }
+}
+
+fn group_files(diff_lines: Vec<DiffLine>) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+
+    for diff_line in diff_lines {
+        if matches!(diff_line, DiffLine::FileHeader { .. }) || files.is_empty() {
+            let is_header = matches!(diff_line, DiffLine::FileHeader { .. });
+            files.push(FileDiff {
+                header: None,
+                lines: Vec::new(),
+                hunks: Vec::new(),
+            });
+            if is_header {
+                files.last_mut().unwrap().header = Some(diff_line);
+                continue;
+            }
+        }
+
+        let file = files.last_mut().unwrap();
+        match (&diff_line, file.hunks.last_mut()) {
+            (DiffLine::HunkHeader { .. }, _) => file.hunks.push(Hunk {
+                header: diff_line,
+                lines: Vec::new(),
+            }),
+            (_, Some(hunk)) => hunk.lines.push(diff_line),
+            (_, None) => file.lines.push(diff_line),
+        }
+    }
+
+    files
+}
+
+fn get_extension(path: &str) -> Option<&str> {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+}
+
+fn is_unity_yaml(path: &str) -> bool {
+    get_extension(path).is_some_and(|extension| UNITY_YAML_EXTENSIONS.contains(&extension))
+}
+
+fn get_changed_content(diff_line: &DiffLine) -> Option<&str> {
+    match diff_line {
+        DiffLine::Content { origin, content } if *origin == '+' || *origin == '-' => Some(content),
+        _ => None,
+    }
+}
+
+fn is_changed(diff_line: &DiffLine) -> bool {
+    get_changed_content(diff_line).is_some()
+}
+
+fn is_document_header(diff_line: &DiffLine) -> bool {
+    match diff_line {
+        DiffLine::Content { content, .. } => content.starts_with(DOCUMENT_HEADER_PREFIX),
+        _ => false,
+    }
+}
+
+/// Returns the key of the YAML mapping entry on a line, if any.
+fn get_property_key(content: &str) -> Option<&str> {
+    let line = content.trim_start();
+    let line = line.strip_prefix("- ").unwrap_or(line);
+    let (key, _value) = line.split_once(':')?;
+    if key.is_empty() || key.starts_with(['{', '-', '#']) || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(key)
+}
+
+/// Removes changes of noise properties and lines with file references that only moved within the
+/// hunk.
+fn remove_noise(lines: &mut Vec<DiffLine>) {
+    lines.retain(|diff_line| {
+        get_changed_content(diff_line)
+            .and_then(get_property_key)
+            .is_none_or(|key| !NOISE_PROPERTIES.contains(&key))
+    });
+
+    let mut added = lines
+        .iter()
+        .filter_map(|diff_line| match diff_line {
+            DiffLine::Content {
+                origin: '+',
+                content,
+            } if content.contains("fileID:") => Some(content.trim().to_owned()),
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+    let mut moved = Vec::new();
+    for diff_line in lines.iter() {
+        if let DiffLine::Content {
+            origin: '-',
+            content,
+        } = diff_line
+            && let Some(index) = added.iter().position(|text| text == content.trim())
+        {
+            moved.push(added.swap_remove(index));
+        }
+    }
+
+    // Each moved line is removed once as a deletion and once as an addition.
+    let mut moved_deleted = moved.clone();
+    let mut moved_added = moved;
+    lines.retain(|diff_line| match diff_line {
+        DiffLine::Content {
+            origin: '-',
+            content,
+        } => !take_line(&mut moved_deleted, content),
+        DiffLine::Content {
+            origin: '+',
+            content,
+        } => !take_line(&mut moved_added, content),
+        _ => true,
+    });
+}
+
+/// Removes the line matching `content` from `lines` and returns whether there was one.
+fn take_line(lines: &mut Vec<String>, content: &str) -> bool {
+    match lines.iter().position(|text| text == content.trim()) {
+        Some(index) => {
+            lines.swap_remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Replaces runs of unchanged lines belonging to a single YAML document with a note.
+fn fold_documents(lines: Vec<DiffLine>) -> Vec<DiffLine> {
+    let mut sections: Vec<Vec<DiffLine>> = Vec::new();
+    for diff_line in lines {
+        if is_document_header(&diff_line) || sections.is_empty() {
+            sections.push(Vec::new());
+        }
+        sections.last_mut().unwrap().push(diff_line);
+    }
+
+    let mut folded_lines = Vec::new();
+    for section in sections {
+        if section.iter().any(is_changed) {
+            folded_lines.extend(section);
+            continue;
+        }
+
+        let text = match section.first() {
+            Some(DiffLine::Content { content, .. }) if is_document_header(&section[0]) => format!(
+                "{} unchanged line(s) of {} folded",
+                section.len(),
+                content.trim()
+            ),
+            _ => format!("{} unchanged line(s) folded", section.len()),
+        };
+        folded_lines.push(DiffLine::Note(text));
+    }
+    folded_lines
+}
+
+/// Hides YAML noise of a Unity asset file and folds its unchanged documents.
+fn fold_yaml(file: &mut FileDiff) {
+    let mut hidden_hunks = 0;
+    let hunks = std::mem::take(&mut file.hunks);
+    for mut hunk in hunks {
+        remove_noise(&mut hunk.lines);
+        if !hunk.lines.iter().any(is_changed) {
+            hidden_hunks += 1;
+            continue;
+        }
+        hunk.lines = fold_documents(hunk.lines);
+        file.hunks.push(hunk);
+    }
+
+    if hidden_hunks > 0 {
+        file.lines.push(DiffLine::Note(format!(
+            "{} hunk(s) with only serialization noise hidden",
+            hidden_hunks
+        )));
+    }
+}
+
+/// Returns the number of distinct properties changed in a Unity asset file.
+fn count_changed_properties(file: &FileDiff) -> usize {
+    let mut properties = HashSet::new();
+    for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+        let mut document = None;
+        let mut last_key = None;
+        for diff_line in hunk.lines.iter() {
+            let DiffLine::Content { content, .. } = diff_line else {
+                continue;
+            };
+            if is_document_header(diff_line) {
+                document = Some(content.trim());
+                last_key = None;
+                continue;
+            }
+
+            let key = get_property_key(content).or(last_key);
+            last_key = key;
+            if is_changed(diff_line) {
+                // Lines before the first document header in a hunk belong to a document that
+                // cannot be identified, so they are counted per hunk.
+                let document = document.map_or_else(|| hunk_index.to_string(), str::to_owned);
+                properties.insert((document, key.unwrap_or(content.trim())));
+            }
+        }
+    }
+    properties.len()
+}
+
+fn summarize_asset(file: &FileDiff) -> DiffLine {
+    let path = Path::new(file.path());
+    let kind = get_extension(file.path()).unwrap_or("asset");
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(file.path());
+    let binary_change = file.lines.iter().find_map(|diff_line| match diff_line {
+        DiffLine::Binary { old_file, new_file } => {
+            Some(get_binary_change_text(old_file.as_ref(), new_file.as_ref()))
+        }
+        _ => None,
+    });
+    let text = match binary_change {
+        Some(binary_change) => format!("{} {}: {}", kind, name, binary_change),
+        None => format!(
+            "{} {}: {} properties changed",
+            kind,
+            name,
+            count_changed_properties(file)
+        ),
+    };
+    DiffLine::Note(text)
+}
+
+/// Applies the enabled `filters` to the lines of a package diff.
+pub(crate) fn filter_diff_lines(diff_lines: Vec<DiffLine>, filters: &DiffFilters) -> Vec<DiffLine> {
+    let mut filtered_lines = Vec::new();
+    let mut dropped_meta_files = 0;
+
+    for mut file in group_files(diff_lines) {
+        if filters.drop_meta && file.path().ends_with(".meta") {
+            dropped_meta_files += 1;
+            continue;
+        }
+        if !is_unity_yaml(file.path()) {
+            filtered_lines.extend(file.into_lines());
+            continue;
+        }
+
+        if filters.fold_yaml {
+            fold_yaml(&mut file);
+        }
+        if filters.summarize_assets {
+            filtered_lines.push(summarize_asset(&file));
+        } else {
+            filtered_lines.extend(file.into_lines());
+        }
+    }
+
+    if dropped_meta_files > 0 {
+        filtered_lines.push(DiffLine::Note(format!(
+            "{} .meta file(s) dropped",
+            dropped_meta_files
+        )));
+    }
+    filtered_lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DiffFilter, DiffFilters, count_changed_properties, filter_diff_lines, fold_documents,
+        group_files, remove_noise,
+    };
+    use crate::diff::DiffLine;
+
+    fn file_header(path: &str) -> DiffLine {
+        DiffLine::FileHeader {
+            old_path: path.to_owned(),
+            new_path: path.to_owned(),
+        }
+    }
+
+    fn hunk_header() -> DiffLine {
+        DiffLine::HunkHeader {
+            lines: "@@ -1 +1 @@".to_owned(),
+            context: String::new(),
+            source: None,
+        }
+    }
+
+    fn content(origin: char, text: &str) -> DiffLine {
+        DiffLine::Content {
+            origin,
+            content: format!("{}\n", text),
+        }
+    }
+
+    /// Returns the diff lines as plain text without the file and hunk headers' details.
+    fn get_texts(diff_lines: &[DiffLine]) -> Vec<String> {
+        diff_lines
+            .iter()
+            .map(|diff_line| match diff_line {
+                DiffLine::FileHeader { new_path, .. } => format!("file {}", new_path),
+                DiffLine::HunkHeader { .. } => "hunk".to_owned(),
+                DiffLine::Content { origin, content } => {
+                    format!("{}{}", origin, content.trim_end())
+                }
+                DiffLine::Binary { .. } => "binary".to_owned(),
+                DiffLine::Note(text) => format!("note {}", text),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn removes_noise_properties_and_moved_references() {
+        let mut lines = vec![
+            content(' ', "GameObject:"),
+            content('-', "  m_ObjectHideFlags: 0"),
+            content('+', "  m_ObjectHideFlags: 1"),
+            content('-', "  - component: {fileID: 11}"),
+            content('+', "  m_Name: Player"),
+            content('+', "  - component: {fileID: 11}"),
+            content('-', "  - component: {fileID: 12}"),
+        ];
+        remove_noise(&mut lines);
+        assert_eq!(
+            get_texts(&lines),
+            [
+                " GameObject:",
+                "+  m_Name: Player",
+                "-  - component: {fileID: 12}"
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_unchanged_documents() {
+        let lines = vec![
+            content(' ', "  m_Enabled: 1"),
+            content(' ', "--- !u!1 &100"),
+            content(' ', "GameObject:"),
+            content(' ', "  m_Layer: 0"),
+            content(' ', "--- !u!4 &200"),
+            content('-', "  m_LocalScale: {x: 1, y: 1, z: 1}"),
+            content('+', "  m_LocalScale: {x: 2, y: 2, z: 2}"),
+        ];
+        assert_eq!(
+            get_texts(&fold_documents(lines)),
+            [
+                "note 1 unchanged line(s) folded",
+                "note 3 unchanged line(s) of --- !u!1 &100 folded",
+                " --- !u!4 &200",
+                "-  m_LocalScale: {x: 1, y: 1, z: 1}",
+                "+  m_LocalScale: {x: 2, y: 2, z: 2}",
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_changed_properties_per_document() {
+        let files = group_files(vec![
+            file_header("Player.prefab"),
+            hunk_header(),
+            content('-', "  m_Name: Old"),
+            content(' ', "--- !u!1 &100"),
+            content('-', "  m_Name: A"),
+            content('+', "  m_Name: B"),
+            content(' ', "  m_Children:"),
+            content('+', "  - {fileID: 300}"),
+            content(' ', "--- !u!1 &200"),
+            content('+', "  m_Name: C"),
+            hunk_header(),
+            content('+', "  m_Name: New"),
+        ]);
+        // m_Name before the first header of each hunk, m_Name and m_Children of &100 and m_Name
+        // of &200.
+        assert_eq!(count_changed_properties(&files[0]), 5);
+    }
+
+    #[test]
+    fn filters_package_diffs() {
+        let diff_lines = || {
+            vec![
+                file_header("Runtime/A.cs"),
+                hunk_header(),
+                content('+', "  m_ObjectHideFlags: 1"),
+                file_header("Runtime/A.cs.meta"),
+                hunk_header(),
+                content('-', "guid: 0123"),
+                content('+', "guid: 4567"),
+                file_header("Prefabs/Player.prefab"),
+                hunk_header(),
+                content('+', "  m_ObjectHideFlags: 1"),
+                hunk_header(),
+                content('+', "  m_Name: Player"),
+            ]
+        };
+
+        assert_eq!(
+            get_texts(&filter_diff_lines(
+                diff_lines(),
+                &DiffFilters::new(&[DiffFilter::Meta, DiffFilter::Yaml])
+            )),
+            [
+                "file Runtime/A.cs",
+                "hunk",
+                "+  m_ObjectHideFlags: 1",
+                "file Prefabs/Player.prefab",
+                "note 1 hunk(s) with only serialization noise hidden",
+                "hunk",
+                "+  m_Name: Player",
+                "note 1 .meta file(s) dropped",
+            ]
+        );
+        assert_eq!(
+            get_texts(&filter_diff_lines(
+                diff_lines(),
+                &DiffFilters::new(&[DiffFilter::Summary])
+            )),
+            [
+                "file Runtime/A.cs",
+                "hunk",
+                "+  m_ObjectHideFlags: 1",
+                "file Runtime/A.cs.meta",
+                "hunk",
+                "-guid: 0123",
+                "+guid: 4567",
+                "note prefab Player: 2 properties changed",
+            ]
+        );
+    }
+}
@@ -0,0 +1,524 @@
+use std::fmt::Write;
+
+use owo_colors::OwoColorize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::DiffLine;
+
+/// Number of spaces a tab is expanded to.
+const TAB_WIDTH: usize = 4;
+
+/// Token pairs above which lines are treated as entirely changed instead of diffed word by word.
+const MAX_WORD_DIFF_SIZE: usize = 1 << 20;
+
+/// Layout options of rendered diff output.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RenderOptions {
+    /// Highlight the changed words of changed lines.
+    pub word_diff: bool,
+    /// Show old and new lines next to each other.
+    pub side_by_side: bool,
+    /// Width of the output in columns, used for the side-by-side layout.
+    pub width: usize,
+}
+
+/// Part of a changed line.
+#[derive(PartialEq, Eq, Debug)]
+pub(crate) struct Segment<'a> {
+    pub text: &'a str,
+    /// Whether the text differs between the old and the new line.
+    pub changed: bool,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum TokenKind {
+    Word,
+    Whitespace,
+    Other,
+}
+
+fn get_token_kind(ch: char) -> TokenKind {
+    if ch.is_alphanumeric() || ch == '_' {
+        TokenKind::Word
+    } else if ch.is_whitespace() {
+        TokenKind::Whitespace
+    } else {
+        TokenKind::Other
+    }
+}
+
+/// Splits `text` into words, runs of whitespace and single other characters.
+pub(crate) fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous_kind = None;
+
+    for (index, ch) in text.char_indices() {
+        let kind = get_token_kind(ch);
+        let continues = previous_kind == Some(kind) && kind != TokenKind::Other;
+        if index > start && !continues {
+            tokens.push(&text[start..index]);
+            start = index;
+        }
+        previous_kind = Some(kind);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+/// Joins consecutive tokens with the same changed flag into segments.
+fn get_segments<'a>(tokens: &[&'a str], changed: &[bool], text: &'a str) -> Vec<Segment<'a>> {
+    let mut segments: Vec<Segment<'a>> = Vec::new();
+    let mut offset = 0;
+
+    for (token, &changed) in tokens.iter().zip(changed.iter()) {
+        let end = offset + token.len();
+        match segments.last_mut() {
+            Some(segment) if segment.changed == changed => {
+                let start = end - token.len() - segment.text.len();
+                segment.text = &text[start..end];
+            }
+            _ => segments.push(Segment {
+                text: &text[offset..end],
+                changed,
+            }),
+        }
+        offset = end;
+    }
+
+    segments
+}
+
+/// Compares the words of an old and a new line and returns the segments of both lines with the
+/// tokens that are not part of their longest common subsequence marked as changed.
+pub(crate) fn diff_words<'a>(old: &'a str, new: &'a str) -> (Vec<Segment<'a>>, Vec<Segment<'a>>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let mut old_changed = vec![true; old_tokens.len()];
+    let mut new_changed = vec![true; new_tokens.len()];
+
+    if old_tokens.len() * new_tokens.len() <= MAX_WORD_DIFF_SIZE {
+        // lengths[i][j] is the length of the longest common subsequence of the token suffixes
+        // starting at i and j.
+        let columns = new_tokens.len() + 1;
+        let mut lengths = vec![0usize; (old_tokens.len() + 1) * columns];
+        for i in (0..old_tokens.len()).rev() {
+            for j in (0..new_tokens.len()).rev() {
+                lengths[i * columns + j] = if old_tokens[i] == new_tokens[j] {
+                    lengths[(i + 1) * columns + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * columns + j].max(lengths[i * columns + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < old_tokens.len() && j < new_tokens.len() {
+            if old_tokens[i] == new_tokens[j] {
+                old_changed[i] = false;
+                new_changed[j] = false;
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * columns + j] >= lengths[i * columns + j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+
+    (
+        get_segments(&old_tokens, &old_changed, old),
+        get_segments(&new_tokens, &new_changed, new),
+    )
+}
+
+/// Returns the text of a content line without the line ending and with tabs expanded.
+fn get_line_text(content: &str) -> String {
+    content
+        .trim_end_matches(['\r', '\n'])
+        .replace('\t', " ".repeat(TAB_WIDTH).as_str())
+}
+
+/// Piece of a rendered line, styled by the origin of its line and whether it changed.
+pub(crate) struct StyledText {
+    pub text: String,
+    pub origin: char,
+    pub changed: bool,
+}
+
+impl StyledText {
+    fn plain(text: String) -> Self {
+        StyledText {
+            text,
+            origin: ' ',
+            changed: false,
+        }
+    }
+}
+
+/// Line of rendered diff output.
+pub(crate) enum RenderedLine<'a> {
+    /// Diff line shown as is.
+    Diff(&'a DiffLine),
+    /// Line laid out from styled pieces.
+    Text(Vec<StyledText>),
+}
+
+fn style_text(text: &str, origin: char, changed: bool) -> String {
+    match (origin, changed) {
+        ('-', false) => text.red().to_string(),
+        ('-', true) => text.red().reversed().to_string(),
+        ('+', false) => text.green().to_string(),
+        ('+', true) => text.green().reversed().to_string(),
+        _ => text.to_string(),
+    }
+}
+
+/// Returns the longest prefix of `text` that fits into `columns` columns, along with its width.
+fn truncate_text(text: &str, columns: usize) -> (String, usize) {
+    let mut prefix = String::new();
+    let mut width = 0;
+    for ch in text.chars() {
+        let char_width = ch.width().unwrap_or(0);
+        if width + char_width > columns {
+            break;
+        }
+        prefix.push(ch);
+        width += char_width;
+    }
+    (prefix, width)
+}
+
+/// Renders a line from its segments, truncated to `width` columns if given.
+fn render_segments(origin: char, segments: &[Segment], width: Option<usize>) -> Vec<StyledText> {
+    let mut pieces = vec![StyledText {
+        text: origin.to_string(),
+        origin,
+        changed: false,
+    }];
+    pieces.push(StyledText::plain(" ".to_owned()));
+
+    let mut remaining = width.map(|width| width.saturating_sub(2));
+    for segment in segments.iter() {
+        let text = match remaining {
+            Some(columns) => {
+                let (text, text_width) = truncate_text(segment.text, columns);
+                remaining = Some(columns - text_width);
+                text
+            }
+            None => segment.text.to_owned(),
+        };
+        pieces.push(StyledText {
+            text,
+            origin,
+            changed: segment.changed,
+        });
+    }
+
+    pieces
+}
+
+/// Pads the rendered line `pieces` with spaces to `width` columns.
+fn pad_pieces(mut pieces: Vec<StyledText>, width: usize) -> Vec<StyledText> {
+    let columns = pieces.iter().map(|piece| piece.text.width()).sum::<usize>();
+    if columns < width {
+        pieces.push(StyledText::plain(" ".repeat(width - columns)));
+    }
+    pieces
+}
+
+fn get_unchanged_segments(text: &str) -> Vec<Segment<'_>> {
+    vec![Segment {
+        text,
+        changed: false,
+    }]
+}
+
+/// Block of diff output rendered together.
+enum Block<'a> {
+    /// Line rendered as is, spanning the whole width.
+    Line(&'a DiffLine),
+    Context(String),
+    /// Consecutive deleted lines followed by consecutive added lines.
+    Change {
+        deleted: Vec<String>,
+        added: Vec<String>,
+    },
+}
+
+fn get_blocks(diff_lines: &[DiffLine]) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+
+    for diff_line in diff_lines.iter() {
+        let DiffLine::Content { origin, content } = diff_line else {
+            blocks.push(Block::Line(diff_line));
+            continue;
+        };
+
+        let text = get_line_text(content);
+        match (*origin, blocks.last_mut()) {
+            ('-', Some(Block::Change { deleted, added })) if added.is_empty() => deleted.push(text),
+            ('+', Some(Block::Change { added, .. })) => added.push(text),
+            ('-', _) => blocks.push(Block::Change {
+                deleted: vec![text],
+                added: Vec::new(),
+            }),
+            ('+', _) => blocks.push(Block::Change {
+                deleted: Vec::new(),
+                added: vec![text],
+            }),
+            (' ', _) => blocks.push(Block::Context(text)),
+            _ => blocks.push(Block::Line(diff_line)),
+        }
+    }
+
+    blocks
+}
+
+/// Returns the segments of the deleted and added line at the same index of a change block, or
+/// `None` for a side without a line at the index.
+fn get_change_segments<'a>(
+    deleted: Option<&'a String>,
+    added: Option<&'a String>,
+    word_diff: bool,
+) -> (Option<Vec<Segment<'a>>>, Option<Vec<Segment<'a>>>) {
+    match (deleted, added) {
+        (Some(deleted), Some(added)) if word_diff => {
+            let (deleted_segments, added_segments) = diff_words(deleted, added);
+            (Some(deleted_segments), Some(added_segments))
+        }
+        _ => (
+            deleted.map(|text| get_unchanged_segments(text)),
+            added.map(|text| get_unchanged_segments(text)),
+        ),
+    }
+}
+
+fn render_unified<'a>(blocks: &[Block<'a>], options: &RenderOptions) -> Vec<RenderedLine<'a>> {
+    let mut rendered_lines = Vec::new();
+    for block in blocks.iter() {
+        match block {
+            Block::Line(diff_line) => rendered_lines.push(RenderedLine::Diff(diff_line)),
+            Block::Context(text) => rendered_lines.push(RenderedLine::Text(render_segments(
+                ' ',
+                &get_unchanged_segments(text),
+                None,
+            ))),
+            Block::Change { deleted, added } => {
+                let mut lines = Vec::new();
+                for index in 0..deleted.len().max(added.len()) {
+                    lines.push(get_change_segments(
+                        deleted.get(index),
+                        added.get(index),
+                        options.word_diff,
+                    ));
+                }
+                for deleted_segments in lines.iter().filter_map(|(old, _)| old.as_ref()) {
+                    rendered_lines.push(RenderedLine::Text(render_segments(
+                        '-',
+                        deleted_segments,
+                        None,
+                    )));
+                }
+                for added_segments in lines.iter().filter_map(|(_, new)| new.as_ref()) {
+                    rendered_lines.push(RenderedLine::Text(render_segments(
+                        '+',
+                        added_segments,
+                        None,
+                    )));
+                }
+            }
+        }
+    }
+    rendered_lines
+}
+
+fn render_side_by_side<'a>(blocks: &[Block<'a>], options: &RenderOptions) -> Vec<RenderedLine<'a>> {
+    let separator = " │ ";
+    let column_width = options.width.saturating_sub(separator.width()) / 2;
+
+    let get_columns = |left: Vec<StyledText>, right: Vec<StyledText>| {
+        let mut pieces = pad_pieces(left, column_width);
+        pieces.push(StyledText::plain(separator.to_owned()));
+        pieces.extend(right);
+        RenderedLine::Text(pieces)
+    };
+
+    let mut rendered_lines = Vec::new();
+    for block in blocks.iter() {
+        match block {
+            Block::Line(diff_line) => rendered_lines.push(RenderedLine::Diff(diff_line)),
+            Block::Context(text) => {
+                let segments = get_unchanged_segments(text);
+                rendered_lines.push(get_columns(
+                    render_segments(' ', &segments, Some(column_width)),
+                    render_segments(' ', &segments, Some(column_width)),
+                ));
+            }
+            Block::Change { deleted, added } => {
+                for index in 0..deleted.len().max(added.len()) {
+                    let (deleted_segments, added_segments) = get_change_segments(
+                        deleted.get(index),
+                        added.get(index),
+                        options.word_diff,
+                    );
+                    let left = deleted_segments.map_or_else(Vec::new, |segments| {
+                        render_segments('-', &segments, Some(column_width))
+                    });
+                    let right = added_segments.map_or_else(Vec::new, |segments| {
+                        render_segments('+', &segments, Some(column_width))
+                    });
+                    rendered_lines.push(get_columns(left, right));
+                }
+            }
+        }
+    }
+    rendered_lines
+}
+
+/// Lays out diff lines with the layout from `options`.
+pub(crate) fn render_lines<'a>(
+    diff_lines: &'a [DiffLine],
+    options: &RenderOptions,
+) -> Vec<RenderedLine<'a>> {
+    if !options.word_diff && !options.side_by_side {
+        return diff_lines.iter().map(RenderedLine::Diff).collect();
+    }
+
+    let blocks = get_blocks(diff_lines);
+    if options.side_by_side {
+        render_side_by_side(&blocks, options)
+    } else {
+        render_unified(&blocks, options)
+    }
+}
+
+/// Renders diff lines as colored text with the layout from `options`.
+pub(crate) fn render(diff_lines: &[DiffLine], options: &RenderOptions) -> String {
+    let mut output = String::new();
+    for rendered_line in render_lines(diff_lines, options) {
+        match rendered_line {
+            RenderedLine::Diff(diff_line) => {
+                let _ = write!(output, "{}", diff_line);
+            }
+            RenderedLine::Text(pieces) => {
+                for piece in pieces.iter() {
+                    output.push_str(style_text(&piece.text, piece.origin, piece.changed).as_str());
+                }
+                output.push('\n');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderOptions, RenderedLine, Segment, diff_words, render_lines, tokenize};
+    use crate::diff::DiffLine;
+
+    fn content(origin: char, text: &str) -> DiffLine {
+        DiffLine::Content {
+            origin,
+            content: format!("{}\n", text),
+        }
+    }
+
+    /// Returns the rendered lines as plain text.
+    fn get_texts(diff_lines: &[DiffLine], options: &RenderOptions) -> Vec<String> {
+        render_lines(diff_lines, options)
+            .into_iter()
+            .map(|rendered_line| match rendered_line {
+                RenderedLine::Diff(diff_line) => diff_line.to_string(),
+                RenderedLine::Text(pieces) => {
+                    pieces.iter().map(|piece| piece.text.as_str()).collect()
+                }
+            })
+            .collect()
+    }
+
+    fn segment(text: &str, changed: bool) -> Segment<'_> {
+        Segment { text, changed }
+    }
+
+    #[test]
+    fn splits_words_whitespace_and_other_characters() {
+        assert_eq!(
+            tokenize("let foo_bar1  =(x);"),
+            ["let", " ", "foo_bar1", "  ", "=", "(", "x", ")", ";"]
+        );
+        assert_eq!(tokenize("größe über"), ["größe", " ", "über"]);
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn marks_words_outside_the_common_subsequence() {
+        let (old, new) = diff_words("let width = 10;", "let height = 10 + 2;");
+        assert_eq!(
+            old,
+            [
+                segment("let ", false),
+                segment("width", true),
+                segment(" = 10;", false)
+            ]
+        );
+        assert_eq!(
+            new,
+            [
+                segment("let ", false),
+                segment("height", true),
+                segment(" = 10", false),
+                segment(" + 2", true),
+                segment(";", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_unpaired_and_blank_changed_lines() {
+        let diff_lines = [
+            content('-', ""),
+            content('-', "removed"),
+            content('+', "added"),
+        ];
+        let options = RenderOptions {
+            word_diff: true,
+            side_by_side: false,
+            width: 80,
+        };
+        assert_eq!(
+            get_texts(&diff_lines, &options),
+            ["- ", "- removed", "+ added"]
+        );
+
+        let diff_lines = [content('+', "first"), content('+', "second")];
+        assert_eq!(get_texts(&diff_lines, &options), ["+ first", "+ second"]);
+    }
+
+    #[test]
+    fn truncates_side_by_side_columns_to_display_width() {
+        let diff_lines = [
+            content(' ', "context"),
+            content('-', "漢字漢字漢字"),
+            content('-', "deleted"),
+            content('+', "added line"),
+        ];
+        let options = RenderOptions {
+            word_diff: false,
+            side_by_side: true,
+            width: 23,
+        };
+        assert_eq!(
+            get_texts(&diff_lines, &options),
+            [
+                "  context  │   context",
+                "- 漢字漢字 │ + added li",
+                "- deleted  │ ",
+            ]
+        );
+    }
+}
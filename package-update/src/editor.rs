@@ -0,0 +1,182 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use package_lib::Version;
+
+use crate::Package;
+use crate::Result;
+use crate::status_to_str;
+
+/// Returns the user's editor command from `$VISUAL` or `$EDITOR`, falling back to a platform
+/// default.
+fn get_editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".to_owned()
+            } else {
+                "vi".to_owned()
+            }
+        })
+}
+
+/// Opens the file at `path` in the user's editor and waits for it to exit. The editor command is
+/// split like a shell would, so quoted programs and arguments may contain spaces.
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = get_editor_command();
+    let args = shlex::split(editor.as_str())
+        .ok_or_else(|| format!("editor command '{}' has unbalanced quotes", editor))?;
+    let Some((program, args)) = args.split_first() else {
+        return Err("editor command is empty".into());
+    };
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|err| format!("failed to launch editor '{}': {}", editor, err))?;
+    if !status.success() {
+        return Err(format!("editor '{}' exited with {}", editor, status).into());
+    }
+
+    Ok(())
+}
+
+/// Writes `text` to a new temporary file ending in `file_name`, opens it in the user's editor and
+/// returns the edited text. The file is only readable by the user and removed afterwards.
+fn edit_text(text: &str, file_name: &str) -> Result<String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("package-update-")
+        .suffix(format!("-{}", file_name).as_str())
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    // Closes the file so that editors that replace it can, while keeping it removed on drop.
+    let path = file.into_temp_path();
+
+    run_editor(&path)?;
+    Ok(fs::read_to_string(&path)?)
+}
+
+fn get_change_message_template(
+    package: &Package,
+    new_version: Version,
+    change_lines: &[String],
+) -> String {
+    let mut text = String::new();
+    for change_line in change_lines.iter() {
+        text.push_str("- ");
+        text.push_str(change_line);
+        text.push('\n');
+    }
+    text.push('\n');
+
+    text.push_str(&format!(
+        "# Enter the change messages for {} {} -> {}.\n",
+        package.name, package.version, new_version
+    ));
+    text.push_str(
+        "# Every unindented line starts a new change message; a leading '- ' or '* ' is\n\
+         # removed. Indented lines continue the previous message, e.g. for markdown\n\
+         # sub-bullets. Lines starting with '#' are ignored and an empty message list\n\
+         # aborts the edit.\n\
+         #\n\
+         # Changed files:\n",
+    );
+    for (path, status) in package.changes.iter() {
+        text.push_str(&format!("#   [{}] {}\n", status_to_str(status), path));
+    }
+
+    text
+}
+
+/// Parses change messages from the edited text. Unindented lines start a new message, indented
+/// lines are appended to the previous message.
+pub(crate) fn parse_change_lines(text: &str) -> Vec<String> {
+    let mut change_lines = Vec::<String>::new();
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace)
+            && let Some(change_line) = change_lines.last_mut()
+        {
+            change_line.push('\n');
+            change_line.push_str(line);
+            continue;
+        }
+
+        let line = line.trim_start();
+        // Trailing whitespace is already trimmed, so an empty bullet is just the marker.
+        let message = ["-", "*"]
+            .iter()
+            .find_map(|bullet| {
+                line.strip_prefix(bullet)
+                    .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+            })
+            .unwrap_or(line)
+            .trim_start();
+        if !message.is_empty() {
+            change_lines.push(message.to_owned());
+        }
+    }
+
+    change_lines
+}
+
+/// Opens the user's editor pre-filled with `change_lines` and information about the package and
+/// returns the edited change messages.
+pub(crate) fn edit_change_lines(
+    package: &Package,
+    new_version: Version,
+    change_lines: &[String],
+) -> Result<Vec<String>> {
+    let template = get_change_message_template(package, new_version, change_lines);
+    let text = edit_text(template.as_str(), "CHANGES.md")?;
+    Ok(parse_change_lines(text.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_change_lines;
+
+    #[test]
+    fn starts_messages_at_unindented_lines() {
+        assert_eq!(
+            parse_change_lines("- Fixed a bug\n* Added a feature\nPlain message\n-  Extra space\n"),
+            [
+                "Fixed a bug",
+                "Added a feature",
+                "Plain message",
+                "Extra space"
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_empty_messages() {
+        assert_eq!(
+            parse_change_lines("# comment\n\n- \n*   \n- Kept\n\n# Changed files:\n#   [M] a.cs\n"),
+            ["Kept"]
+        );
+        assert!(parse_change_lines("# only comments\n\n").is_empty());
+    }
+
+    #[test]
+    fn appends_indented_lines_to_previous_message() {
+        assert_eq!(
+            parse_change_lines("- Title\n  - sub bullet\n\t\tdetails   \n- Next\n"),
+            ["Title\n  - sub bullet\n\t\tdetails", "Next"]
+        );
+        // Indented lines without a previous message start one.
+        assert_eq!(parse_change_lines("  - Indented\n"), ["Indented"]);
+    }
+}
@@ -1,28 +1,42 @@
-use std::fs;
-use std::io::{self, Write};
+use std::error;
+use std::process::ExitCode;
+use std::result;
 
+use clap::ValueEnum;
 use git2::Repository;
 use owo_colors::OwoColorize;
-use serde_json::{Value, json};
-
-use package_lib::{NormalizeLineEndings, Result, Trim, Version, read_json, write_json};
+use package_lib::{Version, print_error};
 
 mod command;
 mod diff;
+mod editor;
 mod options;
 mod package;
+mod pager;
+mod prompt;
+mod release;
+#[cfg(feature = "tui")]
+mod tui;
+mod unity;
 
 use crate::command::*;
 use crate::diff::*;
+use crate::editor::*;
 use crate::options::*;
 use crate::package::*;
+use crate::prompt::*;
+use crate::release::*;
+#[cfg(feature = "tui")]
+use crate::tui::*;
+use crate::unity::*;
 
-fn read_line_from_stdin() -> String {
-    let mut input = String::new();
-    let _ = io::stdin().read_line(&mut input).map_err(|_| input.clear());
-    input.trim();
-    input
-}
+pub(crate) type Result<T> = result::Result<T, Box<dyn error::Error>>;
+
+/// Input at the change message prompt that opens the change messages in an editor.
+const EDIT_CHANGE_LINES_INPUT: &str = ":e";
+
+/// Filename of the change message history inside the repository's git directory.
+const HISTORY_FILENAME: &str = "package-update_history";
 
 fn status_to_str(status: &git2::Status) -> &str {
     if status.is_index_new() || status.is_wt_new() {
@@ -40,96 +54,176 @@ fn status_to_str(status: &git2::Status) -> &str {
     }
 }
 
-fn write_package_manifest(package: &Package, new_version: Version) -> Result<()> {
-    let package_manifest_path = package
-        .path_abs
-        .join(package_lib::PACKAGE_MANIFEST_FILENAME);
+/// Reads change messages from the user. Returns `None` if the user cancelled the update.
+fn read_change_lines(
+    package: &Package,
+    new_version: Version,
+    prompt: &mut Prompt,
+) -> Result<Option<Vec<String>>> {
+    let mut change_lines = Vec::<String>::new();
 
-    let mut package_json: Value = read_json(package_manifest_path.as_path())?;
-    if !package_json.is_object() {
-        return Err("package.json is not an object".into());
-    }
+    println!(
+        "{}",
+        format!(
+            "enter an empty line to finish or '{}' to open an editor",
+            EDIT_CHANGE_LINES_INPUT
+        )
+        .dimmed()
+    );
 
-    package_json["version"] = json!(new_version.to_string());
+    loop {
+        let line = match prompt.read_change_message("enter change message: ")? {
+            Input::Line(line) => line,
+            Input::Eof if !change_lines.is_empty() => break,
+            Input::Interrupted | Input::Eof => {
+                println!("{}", "update cancelled".red());
+                return Ok(None);
+            }
+        };
+
+        if line == EDIT_CHANGE_LINES_INPUT {
+            match edit_change_lines(package, new_version, &change_lines) {
+                Ok(edited_change_lines) if !edited_change_lines.is_empty() => {
+                    return Ok(Some(edited_change_lines));
+                }
+                Ok(_) => println!("{}", "change message required".red()),
+                Err(err) => println!("{}", err.to_string().red()),
+            }
+        } else if line.is_empty() {
+            if !change_lines.is_empty() {
+                break;
+            }
+            println!("{}", "change message required".red());
+        } else {
+            prompt.add_change_message_history(line.as_str())?;
+            change_lines.push(line);
+        }
+    }
 
-    write_json(package_manifest_path, &package_json)
+    Ok(Some(change_lines))
 }
 
-fn write_package_changelog(
-    package: &Package,
-    new_version: Version,
-    change_lines: &[String],
+/// Writes the changes of `release`, or collects them in `file_changes` for a dry run.
+fn apply_release(
+    release: &Release,
     options: &Options,
+    file_changes: &mut Vec<FileChange>,
 ) -> Result<()> {
-    let changelog_path = package.path_abs.join(options.changelog_filename.as_str());
-
-    let mut text = fs::read_to_string(changelog_path.as_path())?;
-    text.trim();
-    text.normalize_line_endings();
-    if !text.is_empty() {
-        text.push('\n');
-    }
-
-    let version_entry = options
-        .changelog_version_entry_template
-        .replace("{version}", new_version.to_string().as_str());
-    text.push_str(version_entry.as_str());
-    text.push('\n');
-
-    for change_line in change_lines.iter() {
-        let change_item = options
-            .changelog_change_item_template
-            .replace("{message}", change_line);
-        text.push_str(change_item.as_str());
-        text.push('\n');
+    for file_change in release.file_changes(options)? {
+        if options.dry_run {
+            file_changes.push(file_change);
+        } else {
+            file_change.write()?;
+        }
     }
-
-    fs::write(changelog_path.as_path(), text)?;
-
     Ok(())
 }
 
-fn update_package(package: &Package, new_version: Version, options: &Options) -> Result<()> {
-    let mut change_lines = Vec::<String>::new();
-
-    loop {
-        print!("{}", "enter change message: ".blue());
-        io::stdout().flush().unwrap();
+/// Decision made for a package at the prompt.
+enum Decision<'a> {
+    Pending,
+    Skip,
+    Release(Release<'a>),
+}
 
-        let line = read_line_from_stdin();
-        if line.is_empty() {
-            if !change_lines.is_empty() {
-                break;
+impl Decision<'_> {
+    fn to_text(&self) -> String {
+        match self {
+            Decision::Pending => "undecided".to_owned(),
+            Decision::Skip => "skip".to_owned(),
+            Decision::Release(release) => {
+                format!("{} -> {}", release.package.version, release.new_version)
             }
-            println!("{}", "change message required".red());
-        } else {
-            change_lines.push(line);
         }
     }
+}
 
-    write_package_changelog(package, new_version, &change_lines, options)?;
-    write_package_manifest(package, new_version)?;
+/// Returns the index of the package with the 1-based `index`, the exact name or the only name
+/// containing the argument.
+fn find_package_index(packages: &[Package], argument: &str) -> Option<usize> {
+    if let Ok(number) = argument.parse::<usize>() {
+        return (1..=packages.len()).contains(&number).then(|| number - 1);
+    }
+    if let Some(index) = packages.iter().position(|package| package.name == argument) {
+        return Some(index);
+    }
+    let mut matches = packages
+        .iter()
+        .enumerate()
+        .filter(|(_, package)| package.name.contains(argument));
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
 
-    Ok(())
+fn print_plan(packages: &[Package], decisions: &[Decision], current_index: usize) {
+    let name_width = packages
+        .iter()
+        .map(|package| package.name.len())
+        .max()
+        .unwrap_or(0);
+    for (index, (package, decision)) in packages.iter().zip(decisions.iter()).enumerate() {
+        let marker = if index == current_index { ">" } else { " " };
+        let decision_text = match decision {
+            Decision::Release(_) => decision.to_text().green().to_string(),
+            _ => decision.to_text(),
+        };
+        println!(
+            "{} {:>3} {:name_width$}  {}",
+            marker,
+            index + 1,
+            package.name.yellow(),
+            decision_text
+        );
+    }
 }
 
-fn process_packages(
-    changed_packages: &[Package],
+fn process_packages<'a>(
+    changed_packages: &'a [Package],
     repo: &Repository,
     options: &Options,
-) -> Result<()> {
+    prompt: &mut Prompt,
+) -> Result<Vec<Release<'a>>> {
     let command_prompt = format!("[{}] > ", get_command_key_text());
 
+    let mut diff_settings = DiffSettings {
+        mode: options.diff_mode,
+        include_untracked: options.untracked,
+        word_diff: options.word_diff,
+        side_by_side: options.side_by_side,
+        filters: DiffFilters::new(&options.diff_filter),
+    };
+
+    let mut decisions = changed_packages
+        .iter()
+        .map(|_| Decision::Pending)
+        .collect::<Vec<Decision>>();
+
     let mut index = 0;
     while index < changed_packages.len() {
         let package = &changed_packages[index];
 
         println!("package name: {}", package.name.yellow());
         println!("package version: {}", package.version.yellow());
+        if let Some(submodule_path) = &package.submodule_path {
+            println!(
+                "package source: {} {}",
+                package.source,
+                submodule_path.display().magenta()
+            );
+        }
+        if !matches!(decisions[index], Decision::Pending) {
+            println!("planned update: {}", decisions[index].to_text().green());
+        }
         println!("changed files:");
         for (path, status) in package.changes.iter() {
             println!("  [{}] {}", status_to_str(status), path.purple());
         }
+        match get_diff_stat(repo, package, &diff_settings) {
+            Ok(diff_stat) => println!("diff stat: {}", diff_stat),
+            Err(err) => println!("diff stat: {}", format!("unavailable ({})", err).red()),
+        }
 
         let command_prompt_text = format!(
             "({}/{}) {}",
@@ -137,32 +231,137 @@ fn process_packages(
             changed_packages.len(),
             command_prompt
         );
-        print!("{}", command_prompt_text.blue());
-        io::stdout().flush().unwrap();
-
-        match get_command_kind_from_input(read_line_from_stdin().as_str()) {
-            Some(CommandKind::UpdateMajor) => {
-                let new_version = package.version.increment_major();
-                println!("update major {} -> {}", package.version, new_version);
-                update_package(package, new_version, options)?;
-            }
-            Some(CommandKind::UpdateMinor) => {
-                let new_version = package.version.increment_minor();
-                println!("update minor {} -> {}", package.version, new_version);
-                update_package(package, new_version, options)?;
-            }
-            Some(CommandKind::UpdatePatch) => {
-                let new_version = package.version.increment_patch();
-                println!("update patch {} -> {}", package.version, new_version);
-                update_package(package, new_version, options)?;
-            }
-            Some(CommandKind::Skip) => {}
-            Some(CommandKind::Diff) => {
-                print_diff(repo, package)?;
+        let input = match prompt.read_command(command_prompt_text.as_str())? {
+            Input::Line(line) => line,
+            Input::Interrupted | Input::Eof => {
+                println!("{}", "interrupted; no updates applied".red());
+                return Ok(Vec::new());
+            }
+        };
+
+        let Some(command) = get_command_from_input(input.as_str()) else {
+            println!("{}", get_command_help().red());
+            continue;
+        };
+        let argument = command.argument.unwrap_or_default();
+
+        match command.kind {
+            CommandKind::UpdateMajor | CommandKind::UpdateMinor | CommandKind::UpdatePatch => {
+                let Some(bump) = command.kind.version_bump() else {
+                    continue;
+                };
+                let new_version = bump.apply(package.version);
+                println!(
+                    "update {} {} -> {}",
+                    bump.name(),
+                    package.version,
+                    new_version
+                );
+                let change_lines = match read_change_lines(package, new_version, prompt) {
+                    Ok(Some(change_lines)) => change_lines,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        println!("{}", err.to_string().red());
+                        continue;
+                    }
+                };
+                decisions[index] = Decision::Release(Release {
+                    package,
+                    new_version,
+                    change_lines,
+                });
+            }
+            CommandKind::Skip => decisions[index] = Decision::Skip,
+            CommandKind::Diff => {
+                if let Err(err) = print_diff(repo, package, &diff_settings) {
+                    println!("{}", err.to_string().red());
+                }
+                continue;
+            }
+            CommandKind::DiffMode => {
+                match DiffMode::from_str(argument, true) {
+                    Ok(mode) => {
+                        diff_settings.mode = mode;
+                        println!("{}", diff_settings);
+                    }
+                    Err(_) => println!("{}", format!("invalid diff mode '{}'", argument).red()),
+                }
+                continue;
+            }
+            CommandKind::DiffUntracked => {
+                diff_settings.include_untracked = !diff_settings.include_untracked;
+                println!("{}", diff_settings);
+                continue;
+            }
+            CommandKind::DiffFilter => {
+                match DiffFilter::from_str(argument, true) {
+                    Ok(filter) => {
+                        diff_settings.filters.toggle(filter);
+                        println!("{}", diff_settings);
+                    }
+                    Err(_) => println!("{}", format!("invalid diff filter '{}'", argument).red()),
+                }
+                continue;
+            }
+            CommandKind::DiffWords => {
+                diff_settings.word_diff = !diff_settings.word_diff;
+                println!("{}", diff_settings);
+                continue;
+            }
+            CommandKind::DiffSideBySide => {
+                diff_settings.side_by_side = !diff_settings.side_by_side;
+                println!("{}", diff_settings);
                 continue;
             }
-            Some(CommandKind::Quit) => break,
-            Some(CommandKind::Help) | None => {
+            CommandKind::Back => {
+                index = index.saturating_sub(1);
+                continue;
+            }
+            CommandKind::Goto => {
+                match find_package_index(changed_packages, argument) {
+                    Some(package_index) => index = package_index,
+                    None => println!("{}", format!("no package matches '{}'", argument).red()),
+                }
+                continue;
+            }
+            CommandKind::UpdateAll => {
+                let Some(bump) = get_version_bump_from_argument(argument) else {
+                    println!("{}", format!("invalid version part '{}'", argument).red());
+                    continue;
+                };
+                println!(
+                    "update {} of {} package(s)",
+                    bump.name(),
+                    changed_packages.len() - index
+                );
+                let new_version = bump.apply(package.version);
+                let change_lines = match read_change_lines(package, new_version, prompt) {
+                    Ok(Some(change_lines)) => change_lines,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        println!("{}", err.to_string().red());
+                        continue;
+                    }
+                };
+                for (package, decision) in changed_packages[index..]
+                    .iter()
+                    .zip(decisions[index..].iter_mut())
+                {
+                    *decision = Decision::Release(Release {
+                        package,
+                        new_version: bump.apply(package.version),
+                        change_lines: change_lines.clone(),
+                    });
+                }
+                print_plan(changed_packages, &decisions, index);
+                break;
+            }
+            CommandKind::Plan => {
+                print_plan(changed_packages, &decisions, index);
+                continue;
+            }
+            CommandKind::Quit => break,
+            CommandKind::Help => {
                 println!("{}", get_command_help().red());
                 continue;
             }
@@ -171,10 +370,98 @@ fn process_packages(
         index += 1;
     }
 
+    Ok(decisions
+        .into_iter()
+        .filter_map(|decision| match decision {
+            Decision::Release(release) => Some(release),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Lets the user decide on the updates of the changed packages and returns the releases.
+fn select_releases<'a>(
+    changed_packages: &'a [Package],
+    repo: &'a Repository,
+    options: &Options,
+) -> Result<Vec<Release<'a>>> {
+    if options.tui {
+        #[cfg(feature = "tui")]
+        return run_tui(changed_packages, repo, options);
+        #[cfg(not(feature = "tui"))]
+        return Err("package-update was built without the tui feature".into());
+    }
+
+    let package_names = changed_packages
+        .iter()
+        .map(|package| package.name.clone())
+        .collect::<Vec<String>>();
+    let history_path = repo.path().join(HISTORY_FILENAME);
+    let mut prompt = Prompt::new(package_names, Some(history_path))?;
+
+    process_packages(changed_packages, repo, options, &mut prompt)
+}
+
+fn print_release_summary(releases: &[Release]) {
+    let header = ("package", "version", "new version");
+    let rows = releases
+        .iter()
+        .map(|release| {
+            (
+                release.package.name.clone(),
+                release.package.version.to_string(),
+                release.new_version.to_string(),
+            )
+        })
+        .collect::<Vec<(String, String, String)>>();
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .fold(header.0.len(), usize::max);
+    let version_width = rows
+        .iter()
+        .map(|(_, version, _)| version.len())
+        .fold(header.1.len(), usize::max);
+
+    let header_text = format!(
+        "{:name_width$}  {:version_width$}  {}",
+        header.0, header.1, header.2
+    );
+    println!("{}", header_text.bold());
+    for (name, version, new_version) in rows.iter() {
+        println!(
+            "{:name_width$}  {:version_width$}  {}",
+            name.yellow(),
+            version,
+            new_version.green()
+        );
+    }
+}
+
+fn print_dry_run(releases: &[Release], file_changes: &[FileChange]) -> Result<()> {
+    println!("{}", "dry run; no files were written".bold());
+
+    if releases.is_empty() {
+        println!("no packages updated");
+        return Ok(());
+    }
+
+    for file_change in file_changes.iter() {
+        print_text_diff(
+            file_change.path.as_path(),
+            file_change.old_text.as_str(),
+            file_change.new_text.as_str(),
+        )?;
+    }
+
+    println!();
+    print_release_summary(releases);
+
     Ok(())
 }
 
-fn main() -> Result<()> {
+fn run() -> Result<()> {
     let options = Options::parse();
 
     let repository_path = options.repository_path.as_path();
@@ -187,7 +474,24 @@ fn main() -> Result<()> {
     }
 
     let packages_path = options.packages_path.as_path();
-    let changed_packages = get_changed_packages(&repo, repository_path, packages_path)?;
+    if let Some(Action::SetUnity(set_options)) = &options.action {
+        let packages = get_packages(
+            &repo,
+            repository_path,
+            packages_path,
+            &options.discovery_options(),
+            options.strict,
+        )?;
+        return set_unity_versions(&packages, set_options, &options);
+    }
+
+    let changed_packages = get_changed_packages(
+        &repo,
+        repository_path,
+        packages_path,
+        &options.discovery_options(),
+        options.strict,
+    )?;
 
     if options.verbose {
         println!("{} package(s) changed", changed_packages.len());
@@ -201,9 +505,29 @@ fn main() -> Result<()> {
 
     if changed_packages.is_empty() {
         println!("no packages changed");
-    } else {
-        process_packages(&changed_packages, &repo, &options)?;
+        return Ok(());
+    }
+
+    let releases = select_releases(&changed_packages, &repo, &options)?;
+
+    let mut file_changes = Vec::<FileChange>::new();
+    for release in releases.iter() {
+        apply_release(release, &options, &mut file_changes)?;
+    }
+
+    if options.dry_run {
+        print_dry_run(&releases, &file_changes)?;
     }
 
     Ok(())
 }
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            print_error(error.as_ref());
+            ExitCode::FAILURE
+        }
+    }
+}
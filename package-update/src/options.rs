@@ -1,26 +1,84 @@
 use std::path::PathBuf;
 
-pub use clap::Parser;
+pub use clap::{Parser, Subcommand};
+
+use package_lib::DiscoveryOptions;
+
+use crate::diff::{DiffFilter, DiffMode};
+use crate::unity::SetUnityOptions;
 
 #[derive(Parser, Debug)]
 #[clap(version, about, long_about = None)]
 pub(crate) struct Options {
     /// Path to the git repository
-    #[clap(short, long, default_value = ".")]
+    #[clap(short, long, default_value = ".", global = true)]
     pub repository_path: PathBuf,
     /// Path to the packages directory, relative to the repository
-    #[clap(short, long, default_value = "Packages")]
+    #[clap(short, long, default_value = "Packages", global = true)]
     pub packages_path: PathBuf,
     /// Name of the changelog file
-    #[clap(long, default_value = "release_notes.md")]
+    #[clap(long, default_value = "release_notes.md", global = true)]
     pub changelog_filename: String,
     /// Changelog entry template
-    #[clap(long, default_value = "\n## Version {version}")]
+    #[clap(long, default_value = "\n## Version {version}", global = true)]
     pub changelog_version_entry_template: String,
     /// Changelog change item template
-    #[clap(long, default_value = " - {message}")]
+    #[clap(long, default_value = " - {message}", global = true)]
     pub changelog_change_item_template: String,
+    /// Changes shown by the diff command
+    #[clap(long, value_enum, default_value = "staged")]
+    pub diff_mode: DiffMode,
+    /// Include untracked files in the diff command
+    #[clap(long)]
+    pub untracked: bool,
+    /// Highlight changed words in the diff command
+    #[clap(long)]
+    pub word_diff: bool,
+    /// Show old and new lines side by side in the diff command
+    #[clap(long)]
+    pub side_by_side: bool,
+    /// Filters applied to the output of the diff command
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub diff_filter: Vec<DiffFilter>,
+    /// Do not write any files; print the changes that would have been made instead
+    #[clap(long, global = true)]
+    pub dry_run: bool,
+    /// Use the full-screen terminal UI; requires the tui feature
+    #[clap(long)]
+    pub tui: bool,
+    /// Skip directories ignored by .gitignore files when searching for packages
+    #[clap(long, global = true)]
+    pub gitignore: bool,
+    /// Glob pattern of directories to skip when searching for packages, relative to the packages
+    /// directory
+    #[clap(long, value_name = "GLOB", global = true)]
+    pub exclude: Vec<String>,
+    /// Also search for packages in git submodules
+    #[clap(long, global = true)]
+    pub submodules: bool,
+    /// Fail if a package manifest cannot be read instead of skipping the package
+    #[clap(long, global = true)]
+    pub strict: bool,
     /// Verbose output
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     pub verbose: bool,
+    #[clap(subcommand)]
+    pub action: Option<Action>,
+}
+
+/// Update applied to packages instead of releasing the changed packages.
+#[derive(Subcommand, Debug)]
+pub(crate) enum Action {
+    /// Set the minimum Unity version of the packages whose name matches the patterns
+    SetUnity(SetUnityOptions),
+}
+
+impl Options {
+    pub fn discovery_options(&self) -> DiscoveryOptions {
+        DiscoveryOptions {
+            respect_gitignore: self.gitignore,
+            exclude: self.exclude.clone(),
+            include_submodules: self.submodules,
+        }
+    }
 }
@@ -5,7 +5,12 @@ use std::str;
 
 use git2::Repository;
 
-use package_lib::{PACKAGE_MANIFEST_FILENAME, Result, Version, find_packages};
+use package_lib::{
+    DiscoveryOptions, PACKAGE_MANIFEST_FILENAME, PackageSource, Version, find_valid_packages,
+    print_discovery_warning,
+};
+
+use crate::Result;
 
 /// Information about a package along with git changes.
 pub(crate) struct Package {
@@ -17,6 +22,10 @@ pub(crate) struct Package {
     pub path: PathBuf,
     /// Absolute path of the package.
     pub path_abs: PathBuf,
+    /// Kind of location the package was found in.
+    pub source: PackageSource,
+    /// Path of the submodule containing the package relative to the repository workdir, if any.
+    pub submodule_path: Option<PathBuf>,
     /// Git changes of the package.
     pub changes: Vec<(String, git2::Status)>,
 }
@@ -28,6 +37,8 @@ impl Package {
             version: package.version,
             path: get_path_in_repo(package.path.as_path(), repo_workdir_path),
             path_abs: package.path,
+            source: package.source,
+            submodule_path: None,
             changes: Vec::new(),
         }
     }
@@ -75,7 +86,13 @@ fn get_package_mut<'a>(
     None
 }
 
-fn set_changes(repo: &Repository, packages: &mut HashMap<String, Package>) -> Result<()> {
+/// Adds the staged changes of `repo` to the packages they belong to. Paths are prefixed with
+/// `path_prefix` for repositories of submodules.
+fn add_changes(
+    repo: &Repository,
+    path_prefix: Option<&str>,
+    packages: &mut HashMap<String, Package>,
+) -> Result<()> {
     let mut status_options = git2::StatusOptions::new();
     status_options
         .show(git2::StatusShow::Index)
@@ -90,33 +107,128 @@ fn set_changes(repo: &Repository, packages: &mut HashMap<String, Package>) -> Re
         .iter()
         .filter(|e| e.status() != git2::Status::CURRENT)
     {
-        let Some(path) = entry.path() else {
-            continue;
+        // Paths that are not valid UTF-8 are shown with replacement characters.
+        let path = String::from_utf8_lossy(entry.path_bytes());
+        let path = match path_prefix {
+            Some(path_prefix) => format!("{}/{}", path_prefix, path),
+            None => path.into_owned(),
         };
-        let Some(package) = get_package_mut(path, packages) else {
+        let Some(package) = get_package_mut(&path, packages) else {
             continue;
         };
-        package.changes.push((path.to_owned(), entry.status()));
+        package.changes.push((path, entry.status()));
         package.changes.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
     }
 
     Ok(())
 }
 
-pub(crate) fn get_changed_packages(
+/// Returns the paths of the initialized submodules of `repo` relative to its workdir.
+fn get_submodule_paths(repo: &Repository) -> Result<Vec<PathBuf>> {
+    Ok(repo
+        .submodules()?
+        .iter()
+        .filter(|submodule| submodule.open().is_ok())
+        .map(|submodule| submodule.path().to_path_buf())
+        .collect())
+}
+
+fn set_changes(
+    repo: &Repository,
+    submodule_paths: &[PathBuf],
+    packages: &mut HashMap<String, Package>,
+) -> Result<()> {
+    add_changes(repo, None, packages)?;
+
+    let Some(workdir_path) = repo.workdir() else {
+        return Ok(());
+    };
+    for submodule_path in submodule_paths.iter() {
+        let submodule_repo = Repository::open(workdir_path.join(submodule_path))?;
+        let path_prefix = submodule_path.to_string_lossy();
+        add_changes(&submodule_repo, Some(&path_prefix), packages)?;
+    }
+
+    Ok(())
+}
+
+/// Finds the packages in `packages_path` of the repository at `repository_path` that can be
+/// updated, keyed by name, along with the paths of the submodules searched.
+fn find_updatable_packages(
     repo: &Repository,
     repository_path: &Path,
     packages_path: &Path,
-) -> Result<Vec<Package>> {
+    discovery_options: &DiscoveryOptions,
+    strict: bool,
+) -> Result<(HashMap<String, Package>, Vec<PathBuf>)> {
     let packages_path = repository_path.join(packages_path);
     let workdir_path = get_repo_workdir_path(repo);
     let workdir_path = workdir_path.as_path();
 
-    let mut packages = find_packages(packages_path.as_path())
-        .map(|package| (package.name.clone(), Package::new(package, workdir_path)))
-        .collect::<HashMap<String, Package>>();
+    let submodule_paths = if discovery_options.include_submodules {
+        get_submodule_paths(repo)?
+    } else {
+        Vec::new()
+    };
+
+    // Tarballs cannot be updated in place, so they are left out.
+    let packages = find_valid_packages(
+        packages_path.as_path(),
+        discovery_options,
+        strict,
+        print_discovery_warning,
+    )?
+    .into_iter()
+    .filter(|package| package.source != PackageSource::Tarball)
+    .map(|package| {
+        let mut package = Package::new(package, workdir_path);
+        package.submodule_path = submodule_paths
+            .iter()
+            .find(|submodule_path| package.path.starts_with(submodule_path))
+            .cloned();
+        (package.name.clone(), package)
+    })
+    .collect::<HashMap<String, Package>>();
+
+    Ok((packages, submodule_paths))
+}
 
-    set_changes(repo, &mut packages)?;
+/// Returns the packages in `packages_path` of the repository at `repository_path` that can be
+/// updated, sorted by name. Their git changes are not read.
+pub(crate) fn get_packages(
+    repo: &Repository,
+    repository_path: &Path,
+    packages_path: &Path,
+    discovery_options: &DiscoveryOptions,
+    strict: bool,
+) -> Result<Vec<Package>> {
+    let (packages, _) = find_updatable_packages(
+        repo,
+        repository_path,
+        packages_path,
+        discovery_options,
+        strict,
+    )?;
+    let mut packages = packages.into_values().collect::<Vec<Package>>();
+    packages.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    Ok(packages)
+}
+
+pub(crate) fn get_changed_packages(
+    repo: &Repository,
+    repository_path: &Path,
+    packages_path: &Path,
+    discovery_options: &DiscoveryOptions,
+    strict: bool,
+) -> Result<Vec<Package>> {
+    let (mut packages, submodule_paths) = find_updatable_packages(
+        repo,
+        repository_path,
+        packages_path,
+        discovery_options,
+        strict,
+    )?;
+    set_changes(repo, &submodule_paths, &mut packages)?;
 
     let mut changed_packages = packages
         .into_values()
@@ -0,0 +1,46 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use crate::Result;
+
+/// Pager used if `$PAGER` is not set.
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Shows `text` in the pager from `$PAGER` if stdout is a terminal, otherwise prints it. Falls back
+/// to printing if the pager cannot be launched.
+pub(crate) fn page_text(text: &str) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        print!("{}", text);
+        return Ok(());
+    }
+
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_owned());
+    let mut args = pager.split_whitespace();
+    let Some(program) = args.next() else {
+        print!("{}", text);
+        return Ok(());
+    };
+
+    let mut command = Command::new(program);
+    command.args(args).stdin(Stdio::piped());
+    // Same defaults as git: quit if the text fits on one screen and keep colors.
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+
+    let Ok(mut child) = command.spawn() else {
+        print!("{}", text);
+        return Ok(());
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Writing fails if the user quits the pager early, which is fine.
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+
+use owo_colors::OwoColorize;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+
+use package_lib::Trim;
+
+use crate::Result;
+use crate::command::{get_command_keys, get_command_names};
+
+/// Line read from a prompt.
+pub(crate) enum Input {
+    Line(String),
+    /// The user pressed Ctrl-C.
+    Interrupted,
+    /// The user pressed Ctrl-D or the input ended.
+    Eof,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum PromptKind {
+    Command,
+    ChangeMessage,
+}
+
+struct PromptHelper {
+    kind: PromptKind,
+    command_words: Vec<String>,
+    package_names: Vec<String>,
+}
+
+impl Completer for PromptHelper {
+    type Candidate = Pair;
+
+    /// Completes command keys and names for the first word and package names for any following word of a
+    /// command. Change messages are not completed.
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        if self.kind != PromptKind::Command {
+            return Ok((0, Vec::new()));
+        }
+
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |index| index + 1);
+        let word = &line[start..];
+        let candidates = if start == 0 {
+            &self.command_words
+        } else {
+            &self.package_names
+        };
+
+        let pairs = candidates
+            .iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: candidate.clone(),
+            })
+            .collect();
+
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for PromptHelper {
+    type Hint = String;
+}
+
+impl Highlighter for PromptHelper {
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
+        &'s self,
+        prompt: &'p str,
+        default: bool,
+    ) -> Cow<'b, str> {
+        if default {
+            Cow::Owned(prompt.blue().to_string())
+        } else {
+            Cow::Borrowed(prompt)
+        }
+    }
+}
+
+impl Validator for PromptHelper {}
+
+impl Helper for PromptHelper {}
+
+/// Line editor for the interactive prompts with a history of change messages.
+pub(crate) struct Prompt {
+    editor: Editor<PromptHelper, DefaultHistory>,
+    history_path: Option<PathBuf>,
+}
+
+impl Prompt {
+    /// Creates a prompt completing the given package names. Change message history is loaded from
+    /// and appended to the file at `history_path`.
+    pub fn new(package_names: Vec<String>, history_path: Option<PathBuf>) -> Result<Self> {
+        let config = Config::builder()
+            .auto_add_history(false)
+            .history_ignore_dups(true)?
+            .max_history_size(1000)?
+            .completion_type(CompletionType::List)
+            .build();
+
+        let mut editor = Editor::with_config(config)?;
+        editor.set_helper(Some(PromptHelper {
+            kind: PromptKind::Command,
+            command_words: [get_command_keys(), get_command_names()].concat(),
+            package_names,
+        }));
+
+        if let Some(history_path) = history_path.as_ref() {
+            match editor.load_history(history_path) {
+                Ok(()) => {}
+                Err(ReadlineError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self {
+            editor,
+            history_path,
+        })
+    }
+
+    fn read(&mut self, prompt: &str, kind: PromptKind) -> Result<Input> {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.kind = kind;
+        }
+
+        // The editor does not print the prompt when reading from a pipe.
+        if !io::stdin().is_terminal() {
+            print!("{}", prompt.blue());
+            io::stdout().flush()?;
+        }
+
+        match self.editor.readline(prompt) {
+            Ok(mut line) => {
+                line.trim();
+                Ok(Input::Line(line))
+            }
+            Err(ReadlineError::Interrupted) => Ok(Input::Interrupted),
+            Err(ReadlineError::Eof) => Ok(Input::Eof),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Reads a command from the user.
+    pub fn read_command(&mut self, prompt: &str) -> Result<Input> {
+        self.read(prompt, PromptKind::Command)
+    }
+
+    /// Reads a change message from the user.
+    pub fn read_change_message(&mut self, prompt: &str) -> Result<Input> {
+        self.read(prompt, PromptKind::ChangeMessage)
+    }
+
+    /// Adds a change message to the history and appends it to the history file.
+    pub fn add_change_message_history(&mut self, change_line: &str) -> Result<()> {
+        if self.editor.add_history_entry(change_line)?
+            && let Some(history_path) = self.history_path.as_ref()
+        {
+            self.editor.append_history(history_path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+use package_lib::{NormalizeLineEndings, Trim, Version, read_to_string};
+
+use crate::Options;
+use crate::Package;
+use crate::Result;
+
+/// Part of the version number incremented by a release.
+#[derive(ValueEnum, PartialEq, Eq, Clone, Copy, Debug)]
+pub(crate) enum VersionBump {
+    /// Increment the major version number
+    Major,
+    /// Increment the minor version number
+    Minor,
+    /// Increment the patch version number
+    Patch,
+}
+
+impl VersionBump {
+    pub fn apply(self, version: Version) -> Version {
+        match self {
+            VersionBump::Major => version.increment_major(),
+            VersionBump::Minor => version.increment_minor(),
+            VersionBump::Patch => version.increment_patch(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VersionBump::Major => "major",
+            VersionBump::Minor => "minor",
+            VersionBump::Patch => "patch",
+        }
+    }
+}
+
+/// Version update of a package along with the change messages for the changelog.
+pub(crate) struct Release<'a> {
+    pub package: &'a Package,
+    pub new_version: Version,
+    pub change_lines: Vec<String>,
+}
+
+/// Pending change of a file's contents.
+pub(crate) struct FileChange {
+    /// Path of the file relative to the repository workdir.
+    pub path: PathBuf,
+    /// Absolute path of the file.
+    pub path_abs: PathBuf,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+impl FileChange {
+    pub fn write(&self) -> Result<()> {
+        fs::write(self.path_abs.as_path(), self.new_text.as_str()).map_err(|source| {
+            package_lib::Error::Io {
+                path: self.path_abs.clone(),
+                operation: "write",
+                source,
+            }
+        })?;
+        Ok(())
+    }
+}
+
+impl Release<'_> {
+    /// Returns the changes to the package manifest and changelog files needed for the release.
+    pub fn file_changes(&self, options: &Options) -> Result<Vec<FileChange>> {
+        let new_version = self.new_version.to_string();
+        Ok(vec![
+            package_changelog_change(self, options)?,
+            package_manifest_change(self.package, |package_json| {
+                package_json["version"] = json!(new_version);
+            })?,
+        ])
+    }
+}
+
+/// Returns the change to the manifest of `package` made by `edit`, which modifies the JSON object
+/// of the manifest. Field order and other fields are kept.
+pub(crate) fn package_manifest_change(
+    package: &Package,
+    edit: impl FnOnce(&mut Value),
+) -> Result<FileChange> {
+    let package_manifest_path = package
+        .path_abs
+        .join(package_lib::PACKAGE_MANIFEST_FILENAME);
+
+    let old_text = read_to_string(package_manifest_path.as_path())?;
+
+    let mut package_json: Value = serde_json::from_str(old_text.as_str())?;
+    if !package_json.is_object() {
+        return Err("package.json is not an object".into());
+    }
+
+    edit(&mut package_json);
+
+    let mut new_text = serde_json::to_string_pretty(&package_json)?;
+    new_text.push('\n');
+
+    Ok(FileChange {
+        path: package.path.join(package_lib::PACKAGE_MANIFEST_FILENAME),
+        path_abs: package_manifest_path,
+        old_text,
+        new_text,
+    })
+}
+
+/// Formats a change message with the change item template. Continuation lines of multi-line
+/// messages are aligned with the start of the message in the template.
+fn format_change_item(template: &str, message: &str) -> String {
+    let mut lines = message.lines();
+    let first_line = lines.next().unwrap_or_default();
+    let mut change_item = template.replace("{message}", first_line);
+
+    let continuation_lines = lines.collect::<Vec<&str>>();
+    if continuation_lines.is_empty() {
+        return change_item;
+    }
+
+    let message_column = template
+        .rsplit('\n')
+        .next()
+        .and_then(|line| {
+            line.find("{message}")
+                .map(|index| line[..index].chars().count())
+        })
+        .unwrap_or(0);
+    // The indent is counted in characters, as lines may be indented with different whitespace.
+    let common_indent = continuation_lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().take_while(|ch| ch.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+
+    for line in continuation_lines {
+        change_item.push('\n');
+        if !line.trim().is_empty() {
+            let indent_len = line
+                .char_indices()
+                .nth(common_indent)
+                .map_or(line.len(), |(index, _)| index);
+            change_item.push_str(" ".repeat(message_column).as_str());
+            change_item.push_str(&line[indent_len..]);
+        }
+    }
+
+    change_item
+}
+
+/// Returns the change to the changelog of the package of `release` that adds an entry for the new
+/// version with its change messages.
+pub(crate) fn package_changelog_change(release: &Release, options: &Options) -> Result<FileChange> {
+    let package = release.package;
+    let changelog_path = package.path_abs.join(options.changelog_filename.as_str());
+
+    let old_text = read_to_string(changelog_path.as_path())?;
+
+    let mut text = old_text.clone();
+    text.trim();
+    text.normalize_line_endings();
+    if !text.is_empty() {
+        text.push('\n');
+    }
+
+    let version_entry = options
+        .changelog_version_entry_template
+        .replace("{version}", release.new_version.to_string().as_str());
+    text.push_str(version_entry.as_str());
+    text.push('\n');
+
+    for change_line in release.change_lines.iter() {
+        let change_item =
+            format_change_item(options.changelog_change_item_template.as_str(), change_line);
+        text.push_str(change_item.as_str());
+        text.push('\n');
+    }
+
+    Ok(FileChange {
+        path: package.path.join(options.changelog_filename.as_str()),
+        path_abs: changelog_path,
+        old_text,
+        new_text: text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_change_item;
+
+    #[test]
+    fn aligns_continuation_lines_with_message() {
+        assert_eq!(
+            format_change_item(
+                " - {message}",
+                "Fixed a bug\n  in the parser\n\n    details"
+            ),
+            " - Fixed a bug\n   in the parser\n\n     details"
+        );
+    }
+
+    #[test]
+    fn strips_mixed_unicode_indent_at_char_boundaries() {
+        assert_eq!(
+            format_change_item(" - {message}", "Title\n\u{3000}wide\n  narrow"),
+            " - Title\n   wide\n    narrow"
+        );
+    }
+}
@@ -0,0 +1,631 @@
+use std::result;
+
+use git2::Repository;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use unicode_width::UnicodeWidthStr;
+
+use crate::Options;
+use crate::Package;
+use crate::Result;
+use crate::diff::{
+    DiffFilters, DiffLine, DiffMode, DiffSettings, RenderedLine, get_binary_change_text, get_diff,
+    render_lines,
+};
+use crate::editor::parse_change_lines;
+use crate::release::{Release, VersionBump};
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Focus {
+    Packages,
+    Diff,
+    Changelog,
+}
+
+impl Focus {
+    fn next(self) -> Self {
+        match self {
+            Focus::Packages => Focus::Diff,
+            Focus::Diff => Focus::Changelog,
+            Focus::Changelog => Focus::Packages,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Focus::Packages => Focus::Changelog,
+            Focus::Diff => Focus::Packages,
+            Focus::Changelog => Focus::Diff,
+        }
+    }
+}
+
+/// Minimal multi-line text editor for the change messages of a package.
+#[derive(Default)]
+struct TextEditor {
+    lines: Vec<String>,
+    row: usize,
+    /// Cursor column in characters.
+    column: usize,
+}
+
+impl TextEditor {
+    fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines.get(row).map_or(0, |line| line.chars().count())
+    }
+
+    /// Returns the display width of the text before the cursor, where the cursor is drawn.
+    fn cursor_x(&self) -> usize {
+        match self.lines.get(self.row) {
+            Some(line) => line[..self.byte_index()].width(),
+            None => 0,
+        }
+    }
+
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.column)
+            .map_or(line.len(), |(index, _)| index)
+    }
+
+    fn ensure_line(&mut self) {
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn insert_char(&mut self, ch: char) {
+        self.ensure_line();
+        let index = self.byte_index();
+        self.lines[self.row].insert(index, ch);
+        self.column += 1;
+    }
+
+    fn insert_newline(&mut self) {
+        self.ensure_line();
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        self.row += 1;
+        self.column = 0;
+        self.lines.insert(self.row, rest);
+    }
+
+    fn delete_backward(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.column = self.line_len(self.row);
+            self.lines[self.row].push_str(line.as_str());
+        }
+    }
+
+    fn delete_forward(&mut self) {
+        if self.column < self.line_len(self.row) {
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(line.as_str());
+        }
+    }
+
+    fn move_left(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.column = self.line_len(self.row);
+        }
+    }
+
+    fn move_right(&mut self) {
+        if self.column < self.line_len(self.row) {
+            self.column += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.column = 0;
+        }
+    }
+
+    fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.column = self.column.min(self.line_len(self.row));
+        }
+    }
+
+    fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.column = self.column.min(self.line_len(self.row));
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(ch) => self.insert_char(ch),
+            KeyCode::Enter => self.insert_newline(),
+            KeyCode::Backspace => self.delete_backward(),
+            KeyCode::Delete => self.delete_forward(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Home => self.column = 0,
+            KeyCode::End => self.column = self.line_len(self.row),
+            _ => {}
+        }
+    }
+}
+
+/// Release decision of a single package.
+#[derive(Default)]
+struct PackageState {
+    bump: Option<VersionBump>,
+    editor: TextEditor,
+}
+
+enum Outcome {
+    Apply,
+    Quit,
+}
+
+struct App<'a> {
+    packages: &'a [Package],
+    repo: &'a Repository,
+    states: Vec<PackageState>,
+    list_state: ListState,
+    focus: Focus,
+    diff_settings: DiffSettings,
+    /// Diff of the selected package, or the error message if it could not be read.
+    diff: result::Result<Vec<DiffLine>, String>,
+    /// Lines of the diff as rendered for the width in `diff_width`.
+    diff_lines: Vec<Line<'static>>,
+    /// Width the diff lines were rendered for, or 0 if they need to be rendered again.
+    diff_width: u16,
+    diff_scroll: u16,
+    message: Option<String>,
+    /// Whether quitting waits for confirmation, as it discards the decisions made so far.
+    confirm_quit: bool,
+}
+
+fn push_diff_text_lines(diff_line: &DiffLine, text_lines: &mut Vec<Line<'static>>) {
+    match diff_line {
+        DiffLine::FileHeader { old_path, new_path } => {
+            text_lines.push(Line::from(format!("--- a/{}", old_path)).bold());
+            text_lines.push(Line::from(format!("+++ b/{}", new_path)).bold());
+        }
+        DiffLine::HunkHeader {
+            lines,
+            context,
+            source,
+        } => {
+            let mut spans = vec![
+                Span::styled(lines.clone(), Style::new().fg(Color::LightCyan)),
+                Span::raw(" "),
+            ];
+            if let Some(source) = source {
+                spans.push(Span::styled(
+                    format!("[{}] ", source.name()),
+                    Style::new().fg(Color::Magenta),
+                ));
+            }
+            spans.push(Span::raw(context.clone()));
+            text_lines.push(Line::from(spans));
+        }
+        DiffLine::Content { origin, content } => {
+            let text = format!("{} {}", origin, content.trim_end_matches(['\r', '\n']))
+                .replace('\t', "    ");
+            text_lines.push(Line::styled(text, get_origin_style(*origin, false)));
+        }
+        DiffLine::Binary { old_file, new_file } => text_lines.push(Line::from(
+            get_binary_change_text(old_file.as_ref(), new_file.as_ref()),
+        )),
+        DiffLine::Note(text) => text_lines.push(Line::from(text.clone()).italic().cyan()),
+    }
+}
+
+/// Returns the style of text on a line with `origin`, highlighted if the text `changed`.
+fn get_origin_style(origin: char, changed: bool) -> Style {
+    let style = match origin {
+        '+' => Style::new().fg(Color::Green),
+        '-' => Style::new().fg(Color::Red),
+        _ => Style::new(),
+    };
+    if changed {
+        style.add_modifier(Modifier::REVERSED)
+    } else {
+        style
+    }
+}
+
+/// Renders diff lines with the same layout as the diffs shown from the prompt.
+fn get_diff_text_lines(
+    diff_lines: &[DiffLine],
+    settings: &DiffSettings,
+    width: u16,
+) -> Vec<Line<'static>> {
+    let mut text_lines = Vec::new();
+    for rendered_line in render_lines(diff_lines, &settings.render_options(width as usize)) {
+        match rendered_line {
+            RenderedLine::Diff(diff_line) => push_diff_text_lines(diff_line, &mut text_lines),
+            RenderedLine::Text(pieces) => text_lines.push(Line::from(
+                pieces
+                    .into_iter()
+                    .map(|piece| {
+                        Span::styled(piece.text, get_origin_style(piece.origin, piece.changed))
+                    })
+                    .collect::<Vec<Span>>(),
+            )),
+        }
+    }
+    text_lines
+}
+
+impl<'a> App<'a> {
+    fn new(packages: &'a [Package], repo: &'a Repository, options: &Options) -> Self {
+        let mut app = Self {
+            packages,
+            repo,
+            states: packages.iter().map(|_| PackageState::default()).collect(),
+            list_state: ListState::default(),
+            focus: Focus::Packages,
+            diff_settings: DiffSettings {
+                mode: options.diff_mode,
+                include_untracked: options.untracked,
+                word_diff: options.word_diff,
+                side_by_side: options.side_by_side,
+                filters: DiffFilters::new(&options.diff_filter),
+            },
+            diff: Ok(Vec::new()),
+            diff_lines: Vec::new(),
+            diff_width: 0,
+            diff_scroll: 0,
+            message: None,
+            confirm_quit: false,
+        };
+        app.select(0);
+        app
+    }
+
+    fn selected(&self) -> usize {
+        self.list_state.selected().unwrap_or(0)
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.packages.len().saturating_sub(1));
+        self.list_state.select(Some(index));
+        self.update_diff();
+    }
+
+    fn update_diff(&mut self) {
+        let package = &self.packages[self.selected()];
+        self.diff_scroll = 0;
+        self.diff =
+            get_diff(self.repo, package, &self.diff_settings).map_err(|err| err.to_string());
+        self.diff_width = 0;
+    }
+
+    /// Renders the diff again if the layout changed or the diff pane is now `width` columns wide.
+    fn render_diff(&mut self, width: u16) {
+        if self.diff_width == width {
+            return;
+        }
+        self.diff_width = width;
+        self.diff_lines = match &self.diff {
+            Ok(diff_lines) => get_diff_text_lines(diff_lines, &self.diff_settings, width),
+            Err(message) => vec![Line::styled(message.clone(), Style::new().fg(Color::Red))],
+        };
+    }
+
+    fn set_bump(&mut self, bump: Option<VersionBump>) {
+        let index = self.selected();
+        self.states[index].bump = bump;
+        if bump.is_some() {
+            self.focus = Focus::Changelog;
+        }
+    }
+
+    /// Returns whether a version bump or change message was entered for any package.
+    fn has_decisions(&self) -> bool {
+        self.states
+            .iter()
+            .any(|state| state.bump.is_some() || !state.editor.text().trim().is_empty())
+    }
+
+    /// Returns the releases of all packages with a version bump or the index of the first package
+    /// that is missing its change messages.
+    fn releases(&self) -> result::Result<Vec<Release<'a>>, usize> {
+        let mut releases = Vec::new();
+        for (index, (package, state)) in self.packages.iter().zip(self.states.iter()).enumerate() {
+            let Some(bump) = state.bump else {
+                continue;
+            };
+            let change_lines = parse_change_lines(state.editor.text().as_str());
+            if change_lines.is_empty() {
+                return Err(index);
+            }
+            releases.push(Release {
+                package,
+                new_version: bump.apply(package.version),
+                change_lines,
+            });
+        }
+        Ok(releases)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Outcome> {
+        self.message = None;
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Outcome::Quit);
+        }
+        if self.confirm_quit {
+            self.confirm_quit = false;
+            return (key.code == KeyCode::Char('y')).then_some(Outcome::Quit);
+        }
+        match key.code {
+            KeyCode::Tab => {
+                self.focus = self.focus.next();
+                return None;
+            }
+            KeyCode::BackTab => {
+                self.focus = self.focus.previous();
+                return None;
+            }
+            KeyCode::Esc if self.focus != Focus::Packages => {
+                self.focus = Focus::Packages;
+                return None;
+            }
+            _ => {}
+        }
+
+        match self.focus {
+            Focus::Packages => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.select(self.selected().saturating_sub(1)),
+                KeyCode::Down | KeyCode::Char('j') => self.select(self.selected() + 1),
+                KeyCode::Char('1') => self.set_bump(Some(VersionBump::Major)),
+                KeyCode::Char('2') => self.set_bump(Some(VersionBump::Minor)),
+                KeyCode::Char('3') => self.set_bump(Some(VersionBump::Patch)),
+                KeyCode::Char('s') => self.set_bump(None),
+                KeyCode::Enter => self.focus = Focus::Changelog,
+                KeyCode::Char('a') => match self.releases() {
+                    Ok(_) => return Some(Outcome::Apply),
+                    Err(index) => {
+                        self.select(index);
+                        self.focus = Focus::Changelog;
+                        self.message = Some(format!(
+                            "change message required for {}",
+                            self.packages[index].name
+                        ));
+                    }
+                },
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    if !self.has_decisions() {
+                        return Some(Outcome::Quit);
+                    }
+                    self.confirm_quit = true;
+                    self.message = Some(
+                        "quit without applying any updates? y to quit, any other key to cancel"
+                            .to_owned(),
+                    );
+                }
+                _ => {}
+            },
+            Focus::Diff => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.diff_scroll = self.diff_scroll.saturating_sub(1)
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.diff_scroll = self.diff_scroll.saturating_add(1)
+                }
+                KeyCode::PageUp => self.diff_scroll = self.diff_scroll.saturating_sub(20),
+                KeyCode::PageDown => self.diff_scroll = self.diff_scroll.saturating_add(20),
+                KeyCode::Home | KeyCode::Char('g') => self.diff_scroll = 0,
+                KeyCode::End | KeyCode::Char('G') => {
+                    self.diff_scroll = self.diff_lines.len().saturating_sub(1) as u16
+                }
+                KeyCode::Char('m') => {
+                    self.diff_settings.mode = match self.diff_settings.mode {
+                        DiffMode::Staged => DiffMode::Unstaged,
+                        DiffMode::Unstaged => DiffMode::All,
+                        DiffMode::All => DiffMode::Staged,
+                    };
+                    self.update_diff();
+                }
+                KeyCode::Char('u') => {
+                    self.diff_settings.include_untracked = !self.diff_settings.include_untracked;
+                    self.update_diff();
+                }
+                KeyCode::Char('w') => {
+                    self.diff_settings.word_diff = !self.diff_settings.word_diff;
+                    self.diff_width = 0;
+                }
+                KeyCode::Char('s') => {
+                    self.diff_settings.side_by_side = !self.diff_settings.side_by_side;
+                    self.diff_width = 0;
+                }
+                _ => {}
+            },
+            Focus::Changelog => {
+                let index = self.selected();
+                self.states[index].editor.handle_key(key);
+            }
+        }
+
+        self.diff_scroll = self
+            .diff_scroll
+            .min(self.diff_lines.len().saturating_sub(1) as u16);
+
+        None
+    }
+
+    fn block(&self, title: String, focus: Focus) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.focus == focus {
+            block.border_style(Style::new().fg(Color::Blue))
+        } else {
+            block
+        }
+    }
+
+    fn draw_packages(&mut self, frame: &mut Frame, area: Rect) {
+        let items = self
+            .packages
+            .iter()
+            .zip(self.states.iter())
+            .map(|(package, state)| {
+                let decision = match state.bump {
+                    Some(bump) => Span::styled(
+                        format!(
+                            "{} -> {} ({})",
+                            package.version,
+                            bump.apply(package.version),
+                            bump.name()
+                        ),
+                        Style::new().fg(Color::Green),
+                    ),
+                    None => Span::raw(format!("{} (skip)", package.version)),
+                };
+                ListItem::new(vec![
+                    Line::styled(package.name.clone(), Style::new().fg(Color::Yellow)),
+                    Line::from(vec![Span::raw("  "), decision]),
+                ])
+            })
+            .collect::<Vec<ListItem>>();
+
+        let list = List::new(items)
+            .block(self.block("packages".to_owned(), Focus::Packages))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    fn draw_diff(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_diff(area.width.saturating_sub(2));
+        let package = &self.packages[self.selected()];
+        let paragraph = Paragraph::new(self.diff_lines.clone())
+            .block(self.block(
+                format!("diff {} ({})", package.path.display(), self.diff_settings),
+                Focus::Diff,
+            ))
+            .scroll((self.diff_scroll, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn draw_changelog(&self, frame: &mut Frame, area: Rect) {
+        let index = self.selected();
+        let package = &self.packages[index];
+        let state = &self.states[index];
+
+        let title = match state.bump {
+            Some(bump) => format!(
+                "change messages {} {} -> {}",
+                package.name,
+                package.version,
+                bump.apply(package.version)
+            ),
+            None => format!("change messages {} (skipped)", package.name),
+        };
+
+        let editor = &state.editor;
+        let height = area.height.saturating_sub(2) as usize;
+        let scroll = (editor.row + 1).saturating_sub(height);
+        let paragraph = Paragraph::new(
+            editor
+                .lines
+                .iter()
+                .map(|line| Line::from(line.clone()))
+                .collect::<Vec<Line>>(),
+        )
+        .block(self.block(title, Focus::Changelog))
+        .scroll((scroll as u16, 0));
+        frame.render_widget(paragraph, area);
+
+        if self.focus == Focus::Changelog {
+            frame.set_cursor_position(Position {
+                x: area.x + 1 + editor.cursor_x() as u16,
+                y: area.y + 1 + (editor.row - scroll) as u16,
+            });
+        }
+    }
+
+    fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        let line = if let Some(message) = self.message.as_ref() {
+            Line::styled(message.clone(), Style::new().fg(Color::Red))
+        } else {
+            let help = match self.focus {
+                Focus::Packages => {
+                    "1/2/3 major/minor/patch, s skip, enter edit, a apply, q quit, ctrl-c abort, tab next pane"
+                }
+                Focus::Diff => {
+                    "up/down/pgup/pgdn scroll, m diff mode, u untracked files, w word diff, s side by side, esc back, tab next pane"
+                }
+                Focus::Changelog => {
+                    "one change message per line, indent to continue, esc back, tab next pane"
+                }
+            };
+            Line::styled(help, Style::new().add_modifier(Modifier::DIM))
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main_area, status_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [packages_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main_area);
+        let [diff_area, changelog_area] =
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(detail_area);
+
+        self.draw_packages(frame, packages_area);
+        self.draw_diff(frame, diff_area);
+        self.draw_changelog(frame, changelog_area);
+        self.draw_status(frame, status_area);
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<Outcome> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && let Some(outcome) = self.handle_key(key)
+            {
+                return Ok(outcome);
+            }
+        }
+    }
+}
+
+/// Runs the full-screen terminal UI for `packages` and returns the releases the user applied.
+/// Returns no releases if the user quit.
+pub(crate) fn run_tui<'a>(
+    packages: &'a [Package],
+    repo: &'a Repository,
+    options: &Options,
+) -> Result<Vec<Release<'a>>> {
+    let mut app = App::new(packages, repo, options);
+
+    let mut terminal = ratatui::init();
+    let outcome = app.run(&mut terminal);
+    ratatui::restore();
+
+    match outcome? {
+        Outcome::Apply => Ok(app.releases().unwrap_or_default()),
+        Outcome::Quit => Ok(Vec::new()),
+    }
+}
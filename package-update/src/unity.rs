@@ -0,0 +1,282 @@
+use clap::Args;
+use globset::GlobSet;
+use owo_colors::OwoColorize;
+use serde_json::{Value, json};
+
+use package_lib::{PACKAGE_MANIFEST_FILENAME, UnityVersion, build_glob_set, read_json};
+
+use crate::diff::print_text_diff;
+use crate::release::{
+    FileChange, Release, VersionBump, package_changelog_change, package_manifest_change,
+};
+use crate::{Options, Package, Result};
+
+/// Field of the package manifest with the minimum Unity version, such as `2022.3`.
+const UNITY_FIELD: &str = "unity";
+
+/// Field of the package manifest with the minimum release of the Unity version, such as `21f1`.
+const UNITY_RELEASE_FIELD: &str = "unityRelease";
+
+#[derive(Args, Debug)]
+pub(crate) struct SetUnityOptions {
+    /// Minimum Unity version to set, such as 2022.3 or 2022.3.21f1; without a release, the
+    /// unityRelease field is removed
+    pub unity_version: UnityVersion,
+    /// Glob patterns of the names of the packages to update
+    #[clap(default_value = "*")]
+    pub patterns: Vec<String>,
+    /// Also increment the version of each updated package and add a changelog entry
+    #[clap(long, value_enum)]
+    pub bump: Option<VersionBump>,
+    /// Change message of the changelog entries of bumped packages [default: "Raised the minimum
+    /// Unity version to VERSION"]
+    #[clap(long, requires = "bump")]
+    pub message: Option<String>,
+}
+
+impl SetUnityOptions {
+    /// Returns the matcher of the package name patterns.
+    fn get_matcher(&self) -> Result<GlobSet> {
+        Ok(build_glob_set(&self.patterns)?)
+    }
+
+    /// Returns the change message of the changelog entries of bumped packages.
+    fn change_message(&self) -> String {
+        self.message.clone().unwrap_or_else(|| {
+            format!("Raised the minimum Unity version to {}", self.unity_version)
+        })
+    }
+}
+
+/// Returns the minimum Unity version of the package manifest `package_json` as written in it,
+/// such as `2022.3.21f1`, or `None` if it has none.
+fn get_unity_text(package_json: &Value) -> Option<String> {
+    let unity = package_json[UNITY_FIELD].as_str()?;
+    Some(match package_json[UNITY_RELEASE_FIELD].as_str() {
+        Some(release) => format!("{}.{}", unity, release),
+        None => unity.to_owned(),
+    })
+}
+
+/// Sets the `unity` and `unityRelease` fields of the package manifest `package_json` to
+/// `unity_version`. The `unityRelease` field is removed if the version has no release.
+fn set_unity_fields(package_json: &mut Value, unity_version: &UnityVersion) {
+    package_json[UNITY_FIELD] = json!(unity_version.package_unity());
+    match unity_version.release {
+        Some(release) => package_json[UNITY_RELEASE_FIELD] = json!(release.to_string()),
+        None => {
+            if let Some(object) = package_json.as_object_mut() {
+                object.remove(UNITY_RELEASE_FIELD);
+            }
+        }
+    }
+}
+
+/// Sets the `unity` and `unityRelease` fields of the `packages` whose name matches the patterns
+/// of `set_options`, and bumps their versions with a changelog entry if requested. Packages that
+/// already have the version are left unchanged.
+pub(crate) fn set_unity_versions(
+    packages: &[Package],
+    set_options: &SetUnityOptions,
+    options: &Options,
+) -> Result<()> {
+    let matcher = set_options.get_matcher()?;
+    let unity_version = set_options.unity_version;
+    let change_message = set_options.change_message();
+
+    let mut file_changes = Vec::<FileChange>::new();
+    let mut update_count = 0;
+    for package in packages.iter() {
+        if !matcher.is_match(package.name.as_str()) {
+            continue;
+        }
+
+        let package_json: Value = read_json(package.path_abs.join(PACKAGE_MANIFEST_FILENAME))?;
+        let old_unity_text = get_unity_text(&package_json);
+        let new_unity_text = unity_version.to_string();
+        if old_unity_text.as_ref() == Some(&new_unity_text) {
+            continue;
+        }
+
+        let release = set_options.bump.map(|bump| Release {
+            package,
+            new_version: bump.apply(package.version),
+            change_lines: vec![change_message.clone()],
+        });
+        let new_version = release.as_ref().map(|release| release.new_version);
+
+        file_changes.push(package_manifest_change(package, |package_json| {
+            set_unity_fields(package_json, &unity_version);
+            if let Some(new_version) = new_version {
+                package_json["version"] = json!(new_version.to_string());
+            }
+        })?);
+        if let Some(release) = &release {
+            file_changes.push(package_changelog_change(release, options)?);
+        }
+
+        let old_unity_text = old_unity_text.unwrap_or_else(|| "none".to_owned());
+        match new_version {
+            Some(new_version) => println!(
+                "{} unity {} -> {}, version {} -> {}",
+                package.name.yellow(),
+                old_unity_text,
+                new_unity_text.green(),
+                package.version,
+                new_version.green()
+            ),
+            None => println!(
+                "{} unity {} -> {}",
+                package.name.yellow(),
+                old_unity_text,
+                new_unity_text.green()
+            ),
+        }
+        update_count += 1;
+    }
+
+    if options.dry_run {
+        println!("{}", "dry run; no files were written".bold());
+        for file_change in file_changes.iter() {
+            print_text_diff(
+                file_change.path.as_path(),
+                file_change.old_text.as_str(),
+                file_change.new_text.as_str(),
+            )?;
+        }
+    } else {
+        for file_change in file_changes.iter() {
+            file_change.write()?;
+        }
+    }
+
+    println!("{} package(s) updated", update_count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use package_lib::{UnityVersion, find_packages};
+    use serde_json::{Value, json};
+
+    use super::{get_unity_text, set_unity_fields, set_unity_versions};
+    use crate::{Action, Options, Package, Parser};
+
+    fn parse(value: &str) -> UnityVersion {
+        value.parse().unwrap()
+    }
+
+    /// Creates a repository directory with a package `com.a.b` whose manifest is `package_json`
+    /// and returns the package.
+    fn create_package(root: &Path, package_json: &str) -> Package {
+        let package_path = root.join("Packages/com.a.b");
+        fs::create_dir_all(package_path.as_path()).unwrap();
+        fs::write(package_path.join("package.json"), package_json).unwrap();
+        fs::write(package_path.join("release_notes.md"), "# Changes\n").unwrap();
+        let package = find_packages(root.join("Packages")).next().unwrap();
+        Package::new(package, root)
+    }
+
+    /// Parses the command line `args` and sets the Unity version of `package` with it.
+    fn run_set_unity(package: Package, args: &[&str]) {
+        let options = Options::try_parse_from(args).unwrap();
+        let Some(Action::SetUnity(set_options)) = &options.action else {
+            panic!("not a set-unity command");
+        };
+        set_unity_versions(&[package], set_options, &options).unwrap();
+    }
+
+    #[test]
+    fn reads_unity_version_as_written() {
+        assert_eq!(
+            get_unity_text(&json!({"unity": "2022.3", "unityRelease": "21f1"})).as_deref(),
+            Some("2022.3.21f1")
+        );
+        assert_eq!(
+            get_unity_text(&json!({"unity": "2022.3"})).as_deref(),
+            Some("2022.3")
+        );
+        assert_eq!(get_unity_text(&json!({"unityRelease": "21f1"})), None);
+    }
+
+    #[test]
+    fn sets_and_removes_unity_release() {
+        let mut package_json = json!({"name": "com.a.b", "unity": "2021.3", "unityRelease": "5f1"});
+        set_unity_fields(&mut package_json, &parse("2022.3.21f1"));
+        assert_eq!(
+            package_json,
+            json!({"name": "com.a.b", "unity": "2022.3", "unityRelease": "21f1"})
+        );
+
+        set_unity_fields(&mut package_json, &parse("2023.1"));
+        assert_eq!(package_json, json!({"name": "com.a.b", "unity": "2023.1"}));
+    }
+
+    #[test]
+    fn bumps_version_and_adds_changelog_entry() {
+        let fixture = tempfile::tempdir().unwrap();
+        let root = fixture.path();
+        let package = create_package(
+            root,
+            r#"{"name": "com.a.b", "version": "1.2.3", "unity": "2022.3", "unityRelease": "5f1"}"#,
+        );
+        let package_path = package.path_abs.clone();
+        run_set_unity(
+            package,
+            &[
+                "package-update",
+                "set-unity",
+                "2022.3",
+                "com.a.*",
+                "--bump",
+                "minor",
+                "--strict",
+                "--changelog-filename",
+                "release_notes.md",
+            ],
+        );
+
+        let text = fs::read_to_string(package_path.join("package.json")).unwrap();
+        let package_json: Value = serde_json::from_str(text.as_str()).unwrap();
+        assert_eq!(
+            package_json,
+            json!({"name": "com.a.b", "version": "1.3.0", "unity": "2022.3"})
+        );
+        assert_eq!(
+            fs::read_to_string(package_path.join("release_notes.md")).unwrap(),
+            "# Changes\n\n## Version 1.3.0\n - Raised the minimum Unity version to 2022.3\n"
+        );
+    }
+
+    #[test]
+    fn leaves_packages_with_the_version_unchanged() {
+        let fixture = tempfile::tempdir().unwrap();
+        let root = fixture.path();
+        let package_json =
+            r#"{"name": "com.a.b", "version": "1.2.3", "unity": "2022.3", "unityRelease": "21f1"}"#;
+        let package = create_package(root, package_json);
+        let package_path = package.path_abs.clone();
+        run_set_unity(
+            package,
+            &[
+                "package-update",
+                "set-unity",
+                "2022.3.21f1",
+                "--bump",
+                "patch",
+            ],
+        );
+
+        assert_eq!(
+            fs::read_to_string(package_path.join("package.json")).unwrap(),
+            package_json
+        );
+        assert_eq!(
+            fs::read_to_string(package_path.join("release_notes.md")).unwrap(),
+            "# Changes\n"
+        );
+    }
+}